@@ -0,0 +1,364 @@
+use std::cell::Cell;
+
+use gloo_net::http::{Request, Response};
+use gloo_storage::{LocalStorage, Storage};
+use serde::{Deserialize, Serialize};
+
+use crate::Todo;
+
+pub const BACKEND_URL: Option<&str> = option_env!("TODO_BACKEND_URL");
+const PENDING_OPS_KEY: &str = "pending_ops";
+
+thread_local! {
+    static FLUSHING: Cell<bool> = const { Cell::new(false) };
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type")]
+pub enum Op {
+    Create { todo: Todo },
+    Toggle { id: String, completed: bool },
+    UpdateTitle { id: String, title: String },
+    Update { todo: Todo },
+    Delete { id: String },
+}
+
+pub fn todos_url(base: &str) -> String {
+    format!("{}/todos", base.trim_end_matches('/'))
+}
+
+pub fn todo_url(base: &str, id: &str) -> String {
+    format!("{}/{}", todos_url(base), id)
+}
+
+fn diff_todo(old: &Todo, new: &Todo) -> Option<Op> {
+    let only_toggled = Todo {
+        completed: old.completed,
+        completed_at: old.completed_at,
+        updated_at: old.updated_at,
+        ..new.clone()
+    } == *old;
+    let only_renamed = Todo {
+        title: old.title.clone(),
+        title_history: old.title_history.clone(),
+        updated_at: old.updated_at,
+        ..new.clone()
+    } == *old;
+    if old == new {
+        None
+    } else if only_toggled {
+        Some(Op::Toggle {
+            id: new.id.clone(),
+            completed: new.completed,
+        })
+    } else if only_renamed {
+        Some(Op::UpdateTitle {
+            id: new.id.clone(),
+            title: new.title.clone(),
+        })
+    } else {
+        Some(Op::Update { todo: new.clone() })
+    }
+}
+
+pub fn diff_todos(old: &[Todo], new: &[Todo]) -> Vec<Op> {
+    let mut ops: Vec<Op> = new
+        .iter()
+        .filter_map(|todo| match old.iter().find(|existing| existing.id == todo.id) {
+            None => Some(Op::Create { todo: todo.clone() }),
+            Some(existing) => diff_todo(existing, todo),
+        })
+        .collect();
+    ops.extend(
+        old.iter()
+            .filter(|todo| !new.iter().any(|kept| kept.id == todo.id))
+            .map(|todo| Op::Delete {
+                id: todo.id.clone(),
+            }),
+    );
+    ops
+}
+
+pub fn apply_op(todos: &[Todo], op: &Op) -> Vec<Todo> {
+    match op {
+        Op::Create { todo } => {
+            let mut new_todos: Vec<Todo> = todos
+                .iter()
+                .filter(|existing| existing.id != todo.id)
+                .cloned()
+                .collect();
+            new_todos.push(todo.clone());
+            new_todos
+        }
+        Op::Toggle { id, completed } => todos
+            .iter()
+            .map(|todo| {
+                if &todo.id == id {
+                    Todo {
+                        completed: *completed,
+                        ..todo.clone()
+                    }
+                } else {
+                    todo.clone()
+                }
+            })
+            .collect(),
+        Op::UpdateTitle { id, title } => todos
+            .iter()
+            .map(|todo| {
+                if &todo.id == id {
+                    Todo {
+                        title: title.clone(),
+                        ..todo.clone()
+                    }
+                } else {
+                    todo.clone()
+                }
+            })
+            .collect(),
+        Op::Update { todo } => todos
+            .iter()
+            .map(|existing| {
+                if existing.id == todo.id {
+                    todo.clone()
+                } else {
+                    existing.clone()
+                }
+            })
+            .collect(),
+        Op::Delete { id } => todos.iter().filter(|todo| &todo.id != id).cloned().collect(),
+    }
+}
+
+pub fn apply_ops(todos: &[Todo], ops: &[Op]) -> Vec<Todo> {
+    ops.iter().fold(todos.to_vec(), |todos, op| apply_op(&todos, op))
+}
+
+pub fn pending_ops_key(profile: &str) -> String {
+    format!("{}:{}", PENDING_OPS_KEY, profile)
+}
+
+pub fn load_pending_ops(key: &str) -> Vec<Op> {
+    LocalStorage::get(key).unwrap_or_default()
+}
+
+fn save_pending_ops(key: &str, ops: &[Op]) -> Result<(), String> {
+    LocalStorage::set(key, ops).map_err(|e| format!("{:?}", e))
+}
+
+pub fn enqueue_ops(key: &str, ops: Vec<Op>) -> Result<(), String> {
+    let mut queue = load_pending_ops(key);
+    queue.extend(ops);
+    save_pending_ops(key, &queue)
+}
+
+fn check_status(response: Response) -> Result<Response, String> {
+    if response.ok() {
+        Ok(response)
+    } else {
+        Err(format!("{} {}", response.status(), response.status_text()))
+    }
+}
+
+pub async fn fetch_todos(base: &str) -> Result<Vec<Todo>, String> {
+    let response = Request::get(&todos_url(base))
+        .send()
+        .await
+        .map_err(|e| e.to_string())?;
+    check_status(response)?
+        .json()
+        .await
+        .map_err(|e| e.to_string())
+}
+
+pub async fn push_op(base: &str, op: &Op) -> Result<(), String> {
+    let request = match op {
+        Op::Create { todo } => Request::post(&todos_url(base)).json(todo),
+        Op::Toggle { id, completed } => Request::patch(&todo_url(base, id))
+            .json(&serde_json::json!({ "completed": completed })),
+        Op::UpdateTitle { id, title } => {
+            Request::patch(&todo_url(base, id)).json(&serde_json::json!({ "title": title }))
+        }
+        Op::Update { todo } => Request::put(&todo_url(base, &todo.id)).json(todo),
+        Op::Delete { id } => Request::delete(&todo_url(base, id)).build(),
+    }
+    .map_err(|e| e.to_string())?;
+    let response = request.send().await.map_err(|e| e.to_string())?;
+    check_status(response).map(|_| ())
+}
+
+pub async fn flush_pending_ops(base: &str, key: &str) -> Result<(), String> {
+    if FLUSHING.with(|flushing| flushing.replace(true)) {
+        return Ok(());
+    }
+    let result = async {
+        loop {
+            let queue = load_pending_ops(key);
+            let Some(op) = queue.first() else {
+                return Ok(());
+            };
+            push_op(base, op).await?;
+            save_pending_ops(key, &queue[1..])?;
+        }
+    }
+    .await;
+    FLUSHING.with(|flushing| flushing.set(false));
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn todo(id: &str, title: &str) -> Todo {
+        Todo {
+            id: id.to_string(),
+            title: title.to_string(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn should_build_urls_without_double_slashes() {
+        assert_eq!(todos_url("http://api.test/"), "http://api.test/todos");
+        assert_eq!(todo_url("http://api.test", "42"), "http://api.test/todos/42");
+    }
+
+    #[test]
+    fn should_diff_created_updated_and_deleted_todos() {
+        let mut starred = todo("5", "Star");
+        let old = vec![
+            todo("1", "Keep"),
+            todo("2", "Rename"),
+            todo("3", "Remove"),
+            todo("4", "Finish"),
+            starred.clone(),
+        ];
+        let mut finished = todo("4", "Finish");
+        finished.completed = true;
+        finished.completed_at = Some(1.0);
+        finished.updated_at = Some(1.0);
+        starred.starred = true;
+        let new = vec![
+            todo("1", "Keep"),
+            todo("2", "Renamed"),
+            finished,
+            starred.clone(),
+            todo("6", "Add"),
+        ];
+        assert_eq!(
+            diff_todos(&old, &new),
+            vec![
+                Op::UpdateTitle {
+                    id: "2".to_string(),
+                    title: "Renamed".to_string()
+                },
+                Op::Toggle {
+                    id: "4".to_string(),
+                    completed: true
+                },
+                Op::Update { todo: starred },
+                Op::Create {
+                    todo: todo("6", "Add")
+                },
+                Op::Delete {
+                    id: "3".to_string()
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn should_not_diff_identical_lists() {
+        let todos = vec![todo("1", "Keep")];
+        assert!(diff_todos(&todos, &todos).is_empty());
+    }
+
+    #[test]
+    fn should_scope_pending_ops_key_per_profile() {
+        assert_eq!(pending_ops_key("default"), "pending_ops:default");
+        assert_ne!(pending_ops_key("work"), pending_ops_key("home"));
+    }
+
+    #[test]
+    fn should_round_trip_queue_serialization() {
+        let queue = vec![
+            Op::Create {
+                todo: todo("1", "Task"),
+            },
+            Op::Toggle {
+                id: "1".to_string(),
+                completed: true,
+            },
+            Op::UpdateTitle {
+                id: "1".to_string(),
+                title: "Renamed".to_string(),
+            },
+            Op::Update {
+                todo: todo("1", "Replaced"),
+            },
+            Op::Delete {
+                id: "1".to_string(),
+            },
+        ];
+        let encoded = serde_json::to_string(&queue).unwrap();
+        let decoded: Vec<Op> = serde_json::from_str(&encoded).unwrap();
+        assert_eq!(decoded, queue);
+    }
+
+    #[test]
+    fn should_apply_each_op() {
+        let todos = vec![todo("1", "Task 1"), todo("2", "Task 2")];
+        let created = apply_op(
+            &todos,
+            &Op::Create {
+                todo: todo("3", "Task 3"),
+            },
+        );
+        assert_eq!(created.len(), 3);
+        assert_eq!(created[2].id, "3");
+        let toggled = apply_op(
+            &todos,
+            &Op::Toggle {
+                id: "2".to_string(),
+                completed: true,
+            },
+        );
+        assert!(toggled[1].completed);
+        assert!(!toggled[0].completed);
+        let renamed = apply_op(
+            &todos,
+            &Op::UpdateTitle {
+                id: "1".to_string(),
+                title: "Renamed".to_string(),
+            },
+        );
+        assert_eq!(renamed[0].title, "Renamed");
+        assert_eq!(renamed[1].title, "Task 2");
+        let deleted = apply_op(
+            &todos,
+            &Op::Delete {
+                id: "1".to_string(),
+            },
+        );
+        assert_eq!(deleted, vec![todo("2", "Task 2")]);
+    }
+
+    #[test]
+    fn should_replay_ops_in_order() {
+        let ops = vec![
+            Op::Create {
+                todo: todo("1", "Task"),
+            },
+            Op::UpdateTitle {
+                id: "1".to_string(),
+                title: "First".to_string(),
+            },
+            Op::UpdateTitle {
+                id: "1".to_string(),
+                title: "Second".to_string(),
+            },
+        ];
+        assert_eq!(apply_ops(&[], &ops), vec![todo("1", "Second")]);
+    }
+}
@@ -0,0 +1,78 @@
+use gloo_timers::callback::Timeout;
+use yew::prelude::*;
+
+const ERROR_DISMISS_MS: u32 = 5000;
+
+pub fn render_error_banner(
+    message: &str,
+    on_retry: Option<Callback<MouseEvent>>,
+    on_dismiss: Callback<MouseEvent>,
+) -> Html {
+    html! {
+        <p class="flex items-center justify-between text-red-500">
+            <span class="flex-grow">{ message }</span>
+            if let Some(on_retry) = on_retry {
+                <button onclick={on_retry} class="ml-2 px-2 text-sm border border-red-500 rounded hover:bg-red-50">
+                    {"Retry save"}
+                </button>
+            }
+            <button onclick={on_dismiss} title="Dismiss" class="ml-2 px-1 hover:text-red-700">
+                {"×"}
+            </button>
+        </p>
+    }
+}
+
+#[derive(Properties, PartialEq)]
+pub struct ErrorBannerProps {
+    pub message: String,
+    #[prop_or_default]
+    pub on_retry: Option<Callback<()>>,
+    pub on_dismiss: Callback<()>,
+}
+
+#[function_component(ErrorBanner)]
+pub fn error_banner(props: &ErrorBannerProps) -> Html {
+    {
+        let on_dismiss = props.on_dismiss.clone();
+        use_effect_with(
+            (props.message.clone(), props.on_retry.is_some()),
+            move |(_, can_retry)| {
+                let timeout = (!*can_retry)
+                    .then(|| Timeout::new(ERROR_DISMISS_MS, move || on_dismiss.emit(())));
+                move || drop(timeout)
+            },
+        );
+    }
+    render_error_banner(
+        &props.message,
+        props.on_retry.as_ref().map(|on_retry| on_retry.reform(|_| ())),
+        props.on_dismiss.reform(|_| ()),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::components::collect_text;
+
+    #[test]
+    fn should_render_error_message_with_dismiss_button() {
+        let mut text = String::new();
+        assert!(collect_text(
+            &render_error_banner("Storage error: full", None, Callback::noop()),
+            &mut text
+        ));
+        assert_eq!(text, "Storage error: full×");
+    }
+
+    #[test]
+    fn should_render_retry_button_when_write_is_pending() {
+        let mut text = String::new();
+        assert!(collect_text(
+            &render_error_banner("Storage error: full", Some(Callback::noop()), Callback::noop()),
+            &mut text
+        ));
+        assert_eq!(text, "Storage error: fullRetry save×");
+    }
+}
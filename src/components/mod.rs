@@ -0,0 +1,18 @@
+pub mod error_banner;
+pub mod todo_form;
+pub mod todo_item;
+pub mod todo_list;
+
+#[cfg(test)]
+pub fn collect_text(node: &yew::Html, text: &mut String) -> bool {
+    use yew::Html;
+    match node {
+        Html::VTag(tag) => tag.children().is_none_or(|child| collect_text(child, text)),
+        Html::VText(vtext) => {
+            text.push_str(&vtext.text);
+            true
+        }
+        Html::VList(list) => list.iter().all(|child| collect_text(child, text)),
+        _ => false,
+    }
+}
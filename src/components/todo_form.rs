@@ -0,0 +1,62 @@
+use yew::prelude::*;
+
+use crate::{render_char_counter, ADD_BUTTON, PLACEHOLDER_TITLE};
+
+#[derive(Properties, PartialEq)]
+pub struct TodoFormProps {
+    pub input_ref: NodeRef,
+    pub due_ref: NodeRef,
+    pub title_len: usize,
+    pub allow_placeholder: bool,
+    pub auto_capitalize: bool,
+    pub on_submit: Callback<SubmitEvent>,
+    pub on_title_input: Callback<InputEvent>,
+    pub on_toggle_placeholder: Callback<MouseEvent>,
+    pub on_toggle_auto_capitalize: Callback<MouseEvent>,
+}
+
+#[function_component(TodoForm)]
+pub fn todo_form(props: &TodoFormProps) -> Html {
+    html! {
+        <form onsubmit={props.on_submit.clone()} class="mb-4">
+            <div class="flex gap-2">
+                <input
+                    type="text"
+                    ref={props.input_ref.clone()}
+                    placeholder="Add a new task (@context first, #tag anywhere)"
+                    oninput={props.on_title_input.clone()}
+                    class="flex-grow p-2 border rounded focus:outline-none focus:ring-2 focus:ring-blue-500"
+                />
+                <input
+                    type="date"
+                    ref={props.due_ref.clone()}
+                    aria-label="Due date"
+                    class="p-2 border rounded text-sm"
+                />
+                { render_char_counter(props.title_len) }
+                <button
+                    type="submit"
+                    class={ADD_BUTTON}
+                >
+                    {"Add"}
+                </button>
+            </div>
+            <label class="flex items-center gap-1 mt-1 text-sm text-gray-600">
+                <input
+                    type="checkbox"
+                    checked={props.allow_placeholder}
+                    onclick={props.on_toggle_placeholder.clone()}
+                />
+                { format!("Add \"{}\" on empty submit", PLACEHOLDER_TITLE) }
+            </label>
+            <label class="flex items-center gap-1 text-sm text-gray-600">
+                <input
+                    type="checkbox"
+                    checked={props.auto_capitalize}
+                    onclick={props.on_toggle_auto_capitalize.clone()}
+                />
+                {"Auto-capitalize new titles"}
+            </label>
+        </form>
+    }
+}
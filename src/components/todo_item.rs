@@ -0,0 +1,561 @@
+use web_sys::{HtmlInputElement, HtmlSelectElement};
+use yew::prelude::*;
+
+use crate::{
+    action_aria_label, category_border_color, display_title, effective_priority, format_date,
+    format_timestamp, humanize_due, is_duplicate_shortcut, is_overdue, render_char_counter,
+    render_title, subtask_progress, timestamp_tooltip, toggle_aria_label, was_recently_edited,
+    Category, DueAction, Priority, Recurrence, Todo, ARCHIVE_BUTTON, BUTTON_CLASS, CANCEL_BUTTON,
+    DUPLICATE_BUTTON, EDITED_WINDOW_MS, EDIT_BUTTON, MOVE_BUTTON, SAVE_BUTTON, SNOOZE_BUTTON,
+    SNOOZE_CHIP_THRESHOLD,
+};
+
+#[derive(Clone, Default, PartialEq)]
+pub struct TodoItemCallbacks {
+    pub on_toggle: Callback<String>,
+    pub on_select: Callback<String>,
+    pub on_toggle_expanded: Callback<String>,
+    pub on_star: Callback<String>,
+    pub on_pin: Callback<String>,
+    pub on_edit: Callback<String>,
+    pub on_update: Callback<String>,
+    pub on_cancel: Callback<MouseEvent>,
+    pub on_edit_title_input: Callback<InputEvent>,
+    pub on_cycle_priority: Callback<(String, Priority)>,
+    pub on_points_change: Callback<(String, String)>,
+    pub on_recurrence_change: Callback<(String, String)>,
+    pub on_category_change: Callback<(String, String)>,
+    pub on_blocked_reason_change: Callback<(String, String)>,
+    pub on_open_due_popover: Callback<(MouseEvent, String)>,
+    pub on_due_action: Callback<(String, DueAction)>,
+    pub on_snooze: Callback<String>,
+    pub on_move_up: Callback<String>,
+    pub on_move_down: Callback<String>,
+    pub on_toggle_review: Callback<String>,
+    pub on_duplicate: Callback<String>,
+    pub on_archive: Callback<String>,
+    pub on_add_subtask: Callback<(String, HtmlInputElement)>,
+    pub on_toggle_subtask: Callback<(String, String)>,
+    pub on_delete_subtask: Callback<(String, String)>,
+    pub on_drag_start: Callback<String>,
+    pub on_drop: Callback<String>,
+}
+
+#[derive(Clone, Default, PartialEq)]
+pub struct EditState {
+    pub input_ref: NodeRef,
+    pub estimate_ref: NodeRef,
+    pub actual_ref: NodeRef,
+    pub title_len: usize,
+    pub error: Option<String>,
+}
+
+#[derive(Properties, Default, PartialEq)]
+pub struct TodoItemProps {
+    pub todo: Todo,
+    pub now: f64,
+    pub utc_offset_ms: f64,
+    pub projection: Option<&'static str>,
+    pub categories: Vec<Category>,
+    pub manual_sort: bool,
+    pub is_editing: bool,
+    pub is_selected: bool,
+    pub is_focused: bool,
+    pub is_expanded: bool,
+    pub is_dragging: bool,
+    pub is_due_popover_open: bool,
+    pub subtask_error: Option<String>,
+    pub edit: EditState,
+    pub callbacks: TodoItemCallbacks,
+}
+
+#[function_component(TodoItem)]
+pub fn todo_item(props: &TodoItemProps) -> Html {
+    render_todo_item(props)
+}
+
+pub fn render_todo_item(props: &TodoItemProps) -> Html {
+    let todo = &props.todo;
+    let callbacks = &props.callbacks;
+    let now = props.now;
+    let is_editing = props.is_editing;
+    let title = todo.title.clone();
+    let completed = todo.completed;
+    let id = todo.id.clone();
+    let emit_id = |callback: &Callback<String>| {
+        let id = id.clone();
+        callback.reform(move |_: MouseEvent| id.clone())
+    };
+    let stored_priority = todo.priority;
+    let shown_priority = effective_priority(todo, now);
+    let overdue = is_overdue(todo, now, props.utc_offset_ms);
+    html! {
+        <li
+            role="listitem"
+            data-id={id.clone()}
+            title={timestamp_tooltip(todo.created_at, todo.updated_at, format_timestamp)}
+            draggable={(props.manual_sort && !is_editing).to_string()}
+            ondragstart={callbacks.on_drag_start.reform({
+                let id = id.clone();
+                move |_: DragEvent| id.clone()
+            })}
+            ondragover={Callback::from(|e: DragEvent| e.prevent_default())}
+            ondrop={callbacks.on_drop.reform({
+                let id = id.clone();
+                move |e: DragEvent| {
+                    e.prevent_default();
+                    id.clone()
+                }
+            })}
+            tabindex="0"
+            onkeydown={{
+                let on_duplicate = callbacks.on_duplicate.clone();
+                let id = id.clone();
+                Callback::from(move |e: KeyboardEvent| {
+                    let row_focused = e.target().is_some() && e.target() == e.current_target();
+                    if is_duplicate_shortcut(&e.key(), e.ctrl_key() || e.meta_key(), row_focused, is_editing) {
+                        e.prevent_default();
+                        on_duplicate.emit(id.clone());
+                    }
+                })
+            }}
+            style={format!("border-left: 4px solid {}", category_border_color(todo.category.as_ref()))}
+            class={classes!("flex", "flex-wrap", "items-center", "p-2", "border", "rounded", "focus:outline-none", "focus:ring-2", "focus:ring-blue-300", props.is_focused.then_some("ring-2 ring-blue-500"), props.is_dragging.then_some("opacity-50"))}
+        >
+            if is_editing {
+                <input
+                    type="text"
+                    ref={props.edit.input_ref.clone()}
+                    value={title}
+                    oninput={callbacks.on_edit_title_input.clone()}
+                    class="flex-grow p-1 border rounded focus:outline-none focus:ring-2 focus:ring-blue-500"
+                />
+                <span class="ml-2">{ render_char_counter(props.edit.title_len) }</span>
+                <input
+                    type="number"
+                    min="0"
+                    placeholder="est. min"
+                    title="Estimated minutes"
+                    ref={props.edit.estimate_ref.clone()}
+                    value={todo.estimate_minutes.map(|minutes| minutes.to_string()).unwrap_or_default()}
+                    class="ml-2 w-16 p-1 text-xs border rounded"
+                />
+                <input
+                    type="number"
+                    min="0"
+                    placeholder="actual min"
+                    title="Actual minutes"
+                    ref={props.edit.actual_ref.clone()}
+                    value={todo.actual_minutes.map(|minutes| minutes.to_string()).unwrap_or_default()}
+                    class="ml-2 w-16 p-1 text-xs border rounded"
+                />
+                <button
+                    onclick={emit_id(&callbacks.on_update)}
+                    class={format!("{} {}", BUTTON_CLASS, SAVE_BUTTON)}
+                >
+                    {"Save"}
+                </button>
+                <button
+                    onclick={callbacks.on_cancel.clone()}
+                    class={format!("{} {}", BUTTON_CLASS, CANCEL_BUTTON)}
+                >
+                    {"Cancel"}
+                </button>
+                if let Some(error) = props.edit.error.clone() {
+                    <p role="alert" class="basis-full mt-1 text-xs text-red-500">{ error }</p>
+                }
+            } else {
+                <button
+                    onclick={emit_id(&callbacks.on_toggle_expanded)}
+                    title={if props.is_expanded { "Hide subtasks" } else { "Show subtasks" }}
+                    class="mr-1 w-4 text-xs text-gray-500"
+                >
+                    { if props.is_expanded { "▾" } else { "▸" } }
+                </button>
+                <input
+                    type="checkbox"
+                    title="Select"
+                    aria-label={action_aria_label("Select", &title)}
+                    checked={props.is_selected}
+                    onclick={emit_id(&callbacks.on_select)}
+                    class="mr-2 accent-gray-500"
+                />
+                <input
+                    type="checkbox"
+                    aria-label={toggle_aria_label(&title, completed)}
+                    checked={completed}
+                    onclick={emit_id(&callbacks.on_toggle)}
+                    class="mr-2"
+                />
+                <button
+                    onclick={emit_id(&callbacks.on_star)}
+                    title={if todo.starred { "Unstar" } else { "Star" }}
+                    class="mr-2 text-yellow-500"
+                >
+                    { if todo.starred { "★" } else { "☆" } }
+                </button>
+                <button
+                    onclick={emit_id(&callbacks.on_pin)}
+                    title={if todo.pinned { "Unpin" } else { "Pin to top" }}
+                    aria-label={action_aria_label(if todo.pinned { "Unpin" } else { "Pin" }, &title)}
+                    class={classes!("mr-2", if todo.pinned { "text-blue-600" } else { "text-gray-400" })}
+                >
+                    {"📌"}
+                </button>
+                if display_title(&title).1 {
+                    <button
+                        onclick={emit_id(&callbacks.on_edit)}
+                        title="Add a title"
+                        class="flex-grow text-left"
+                    >
+                        { render_title(&title, completed, Callback::noop()) }
+                    </button>
+                } else {
+                    { render_title(&title, completed, emit_id(&callbacks.on_edit)) }
+                }
+                if let Some((done, total)) = subtask_progress(todo) {
+                    <span class="ml-2 flex items-center gap-1 text-xs text-gray-500" title="Subtasks done">
+                        { format!("{}/{}", done, total) }
+                        <span class="w-10 h-1 bg-gray-200 rounded">
+                            <span
+                                class="block h-1 bg-green-500 rounded"
+                                style={format!("width: {}%", done * 100 / total)}
+                            />
+                        </span>
+                    </span>
+                }
+                <button
+                    onclick={callbacks.on_cycle_priority.reform({
+                        let id = id.clone();
+                        move |_| (id.clone(), stored_priority)
+                    })}
+                    title={format!("Priority: {}", stored_priority.label())}
+                    class={format!("ml-2 px-1 text-xs rounded {}", shown_priority.badge_class())}
+                >
+                    { if shown_priority == stored_priority {
+                        shown_priority.label().to_string()
+                    } else {
+                        format!("{} ↑", shown_priority.label())
+                    } }
+                </button>
+                <input
+                    type="number"
+                    min="0"
+                    placeholder="pts"
+                    title="Effort points"
+                    value={todo.points.map(|points| points.to_string()).unwrap_or_default()}
+                    onchange={callbacks.on_points_change.reform({
+                        let id = id.clone();
+                        move |e: Event| {
+                            let input = e.target_unchecked_into::<HtmlInputElement>();
+                            (id.clone(), input.value())
+                        }
+                    })}
+                    class="ml-2 w-12 p-1 text-xs border rounded"
+                />
+                <select
+                    title="Repeat"
+                    onchange={callbacks.on_recurrence_change.reform({
+                        let id = id.clone();
+                        move |e: Event| {
+                            let select = e.target_unchecked_into::<HtmlSelectElement>();
+                            (id.clone(), select.value())
+                        }
+                    })}
+                    class="ml-2 p-1 text-xs border rounded"
+                >
+                    <option value="" selected={todo.recurrence.is_none()}>{"↻ none"}</option>
+                    { for Recurrence::ALL.into_iter().map(|recurrence| html! {
+                        <option value={recurrence.value()} selected={todo.recurrence == Some(recurrence)}>
+                            { format!("↻ {}", recurrence.label()) }
+                        </option>
+                    })}
+                </select>
+                if !props.categories.is_empty() {
+                    <select
+                        title="Category"
+                        onchange={callbacks.on_category_change.reform({
+                            let id = id.clone();
+                            move |e: Event| {
+                                let select = e.target_unchecked_into::<HtmlSelectElement>();
+                                (id.clone(), select.value())
+                            }
+                        })}
+                        class="ml-2 p-1 text-xs border rounded"
+                    >
+                        <option value="" selected={todo.category.is_none()}>{"No category"}</option>
+                        { for props.categories.iter().map(|category| html! {
+                            <option
+                                value={category.name.clone()}
+                                selected={todo.category.as_ref().is_some_and(|current| current.name == category.name)}
+                            >
+                                { category.name.clone() }
+                            </option>
+                        })}
+                    </select>
+                }
+                { for todo.tags.iter().map(|tag| html! {
+                    <span class="ml-1 px-1 text-xs text-blue-700 bg-blue-100 rounded">
+                        { format!("#{}", tag) }
+                    </span>
+                })}
+                <input
+                    type="text"
+                    placeholder="blocked by…"
+                    title="Blocked reason"
+                    value={todo.blocked_reason.clone().unwrap_or_default()}
+                    onchange={callbacks.on_blocked_reason_change.reform({
+                        let id = id.clone();
+                        move |e: Event| {
+                            let input = e.target_unchecked_into::<HtmlInputElement>();
+                            (id.clone(), input.value())
+                        }
+                    })}
+                    class="ml-2 w-24 p-1 text-xs border rounded"
+                />
+                if let Some(context) = &todo.context {
+                    <span class="ml-1 px-1 text-xs text-green-800 bg-green-100 rounded" title="Context">
+                        { format!("📍 {}", context) }
+                    </span>
+                }
+                if let Some(reason) = &todo.blocked_reason {
+                    <span class="ml-1 px-1 text-xs text-amber-800 bg-amber-100 rounded">
+                        { format!("🚧 {}", reason) }
+                    </span>
+                }
+                if was_recently_edited(todo, now, EDITED_WINDOW_MS) {
+                    <span class="ml-2 px-1 text-xs text-indigo-700 bg-indigo-100 rounded">
+                        { "edited" }
+                    </span>
+                }
+                if todo.snooze_count >= SNOOZE_CHIP_THRESHOLD {
+                    <span class="ml-2 px-1 text-xs text-gray-500 bg-gray-100 rounded">
+                        { format!("snoozed {}×", todo.snooze_count) }
+                    </span>
+                }
+                <span class="relative ml-2">
+                    <button
+                        onclick={callbacks.on_open_due_popover.reform({
+                            let id = id.clone();
+                            move |e: MouseEvent| (e, id.clone())
+                        })}
+                        class={if overdue {
+                            "px-1 text-xs rounded bg-red-100 text-red-700"
+                        } else if todo.due_at.is_some() {
+                            "px-1 text-xs rounded bg-purple-100 text-purple-800"
+                        } else {
+                            "px-1 text-xs rounded text-gray-400 hover:text-gray-600"
+                        }}
+                    >
+                        { todo.due_at.map_or_else(|| "+ due".to_string(), |due_at| format!("Due {}", format_date(due_at))) }
+                    </button>
+                    if props.is_due_popover_open {
+                        <div
+                            onclick={Callback::from(|e: MouseEvent| e.stop_propagation())}
+                            class="absolute right-0 z-10 mt-1 flex flex-col gap-1 p-2 bg-white border rounded shadow text-sm"
+                        >
+                            { for [("+1d", DueAction::AddDay), ("+1w", DueAction::AddWeek), ("Clear", DueAction::Clear)]
+                                .into_iter()
+                                .map(|(label, action)| {
+                                    let id = id.clone();
+                                    html! {
+                                        <button
+                                            onclick={callbacks.on_due_action.reform(move |_| (id.clone(), action.clone()))}
+                                            class="px-2 py-1 text-left rounded hover:bg-gray-100"
+                                        >
+                                            { label }
+                                        </button>
+                                    }
+                                })
+                            }
+                            <input
+                                type="date"
+                                onchange={callbacks.on_due_action.reform({
+                                    let id = id.clone();
+                                    move |e: Event| {
+                                        let input = e.target_unchecked_into::<HtmlInputElement>();
+                                        (id.clone(), DueAction::Pick(input.value()))
+                                    }
+                                })}
+                                class="p-1 border rounded"
+                            />
+                        </div>
+                    }
+                </span>
+                if let Some(due_at) = todo.due_at {
+                    <span class={classes!("ml-1", "text-xs", if overdue { "text-red-600" } else { "text-gray-500" })}>
+                        { humanize_due(due_at, now) }
+                    </span>
+                }
+                if let Some(projection) = props.projection {
+                    <span class="ml-1 text-xs text-gray-400" title="Projected completion at your recent pace">
+                        { format!("~{}", projection) }
+                    </span>
+                }
+                if todo.due_at.is_some() {
+                    <button
+                        onclick={emit_id(&callbacks.on_snooze)}
+                        aria-label={action_aria_label("Snooze", &todo.title)}
+                        class={format!("{} {}", BUTTON_CLASS, SNOOZE_BUTTON)}
+                    >
+                        {"Snooze"}
+                    </button>
+                }
+                if props.manual_sort {
+                    <button onclick={emit_id(&callbacks.on_move_up)} class={MOVE_BUTTON}>
+                        {"▲"}
+                    </button>
+                    <button onclick={emit_id(&callbacks.on_move_down)} class={MOVE_BUTTON}>
+                        {"▼"}
+                    </button>
+                }
+                <button
+                    onclick={emit_id(&callbacks.on_edit)}
+                    aria-label={action_aria_label("Edit", &todo.title)}
+                    class={format!("{} {}", BUTTON_CLASS, EDIT_BUTTON)}
+                >
+                    {"Edit"}
+                </button>
+                <button
+                    onclick={emit_id(&callbacks.on_toggle_review)}
+                    title="Require a checklist review before completing"
+                    class={classes!("ml-2", "px-1", "text-xs", "rounded", if todo.require_review {
+                        "bg-orange-100 text-orange-800"
+                    } else {
+                        "text-gray-400 hover:text-gray-600"
+                    })}
+                >
+                    {"Review"}
+                </button>
+                <button
+                    onclick={emit_id(&callbacks.on_duplicate)}
+                    aria-label={action_aria_label("Duplicate", &todo.title)}
+                    class={format!("{} {}", BUTTON_CLASS, DUPLICATE_BUTTON)}
+                >
+                    {"Duplicate"}
+                </button>
+                <button
+                    onclick={emit_id(&callbacks.on_archive)}
+                    aria-label={action_aria_label("Archive", &todo.title)}
+                    class={format!("{} {}", BUTTON_CLASS, ARCHIVE_BUTTON)}
+                >
+                    {"Archive"}
+                </button>
+                if props.is_expanded {
+                    <ul class="basis-full ml-6 mt-1 text-sm">
+                        { for todo.subtasks.iter().map(|subtask| {
+                            let toggle_ids = (id.clone(), subtask.id.clone());
+                            let delete_ids = toggle_ids.clone();
+                            html! {
+                                <li class="flex items-center gap-2">
+                                    <input
+                                        type="checkbox"
+                                        checked={subtask.done}
+                                        onclick={callbacks.on_toggle_subtask.reform(move |_| toggle_ids.clone())}
+                                    />
+                                    <span class={classes!("flex-grow", subtask.done.then_some("line-through text-gray-400"))}>
+                                        { subtask.title.clone() }
+                                    </span>
+                                    <button
+                                        onclick={callbacks.on_delete_subtask.reform(move |_| delete_ids.clone())}
+                                        title="Delete subtask"
+                                        class="px-1 text-xs text-red-500 hover:text-red-700"
+                                    >
+                                        {"×"}
+                                    </button>
+                                </li>
+                            }
+                        })}
+                        <li>
+                            <input
+                                type="text"
+                                placeholder="+ subtask"
+                                aria-label={action_aria_label("Add subtask to", &todo.title)}
+                                onkeydown={Callback::from({
+                                    let id = id.clone();
+                                    let on_add_subtask = callbacks.on_add_subtask.clone();
+                                    move |e: KeyboardEvent| {
+                                        if e.key() == "Enter" {
+                                            e.prevent_default();
+                                            let input = e.target_unchecked_into::<HtmlInputElement>();
+                                            on_add_subtask.emit((id.clone(), input.clone()));
+                                            let _ = input.focus();
+                                        }
+                                    }
+                                })}
+                                class="p-1 text-xs border rounded"
+                            />
+                            if let Some(error) = props.subtask_error.clone() {
+                                <p role="alert" class="mt-1 text-xs text-red-500">{ error }</p>
+                            }
+                        </li>
+                    </ul>
+                }
+                if !todo.title_history.is_empty() {
+                    <details class="basis-full mt-1 text-xs text-gray-500">
+                        <summary class="cursor-pointer">
+                            { format!("Previous titles ({})", todo.title_history.len()) }
+                        </summary>
+                        <ul class="ml-4 list-disc">
+                            { for todo.title_history.iter().rev().map(|(changed_at, old_title)| html! {
+                                <li>{ format!("{} — {}", format_timestamp(*changed_at), old_title) }</li>
+                            })}
+                        </ul>
+                    </details>
+                }
+            }
+        </li>
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::components::collect_text;
+
+    fn item(title: &str) -> TodoItemProps {
+        TodoItemProps {
+            todo: Todo {
+                id: "1".to_string(),
+                title: title.to_string(),
+                ..Default::default()
+            },
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn should_render_save_and_cancel_with_error_while_editing() {
+        let props = TodoItemProps {
+            is_editing: true,
+            edit: EditState {
+                error: Some("Title can't be empty".to_string()),
+                ..Default::default()
+            },
+            ..item("Write report")
+        };
+        let mut text = String::new();
+        assert!(collect_text(&render_todo_item(&props), &mut text));
+        assert!(text.contains("SaveCancel"));
+        assert!(text.ends_with("Title can't be empty"));
+    }
+
+    #[test]
+    fn should_render_subtask_error_only_when_expanded() {
+        let collapsed = TodoItemProps {
+            subtask_error: Some("Subtask can't be empty".to_string()),
+            ..item("")
+        };
+        let mut text = String::new();
+        assert!(collect_text(&render_todo_item(&collapsed), &mut text));
+        assert!(!text.contains("Subtask can't be empty"));
+
+        let expanded = TodoItemProps {
+            is_expanded: true,
+            ..collapsed
+        };
+        let mut text = String::new();
+        assert!(collect_text(&render_todo_item(&expanded), &mut text));
+        assert!(text.contains("Subtask can't be empty"));
+    }
+}
@@ -0,0 +1,112 @@
+use std::collections::{HashMap, HashSet};
+
+use yew::prelude::*;
+
+use crate::components::todo_item::{EditState, TodoItem, TodoItemCallbacks, TodoItemProps};
+use crate::{Category, Todo};
+
+#[derive(Clone, Default, PartialEq)]
+pub struct TodoRowContext {
+    pub now: f64,
+    pub utc_offset_ms: f64,
+    pub categories: Vec<Category>,
+    pub manual_sort: bool,
+    pub edit_id: Option<String>,
+    pub selected: HashSet<String>,
+    pub focused_id: Option<String>,
+    pub expanded: HashSet<String>,
+    pub dragging_id: Option<String>,
+    pub due_popover: Option<String>,
+    pub subtask_error: Option<(String, String)>,
+    pub projections: HashMap<String, &'static str>,
+    pub edit: EditState,
+    pub callbacks: TodoItemCallbacks,
+}
+
+#[derive(Properties, PartialEq)]
+pub struct TodoListProps {
+    pub todos: Vec<Todo>,
+    pub label: AttrValue,
+    pub class: Classes,
+    pub rows: TodoRowContext,
+    #[prop_or_default]
+    pub list_ref: NodeRef,
+    #[prop_or_default]
+    pub onscroll: Option<Callback<Event>>,
+    #[prop_or_default]
+    pub onkeydown: Option<Callback<KeyboardEvent>>,
+}
+
+#[function_component(TodoList)]
+pub fn todo_list(props: &TodoListProps) -> Html {
+    html! {
+        <ul
+            role="list"
+            aria-label={props.label.clone()}
+            ref={props.list_ref.clone()}
+            onscroll={props.onscroll.clone()}
+            class={props.class.clone()}
+            tabindex={props.onkeydown.as_ref().map(|_| "0")}
+            onkeydown={props.onkeydown.clone()}
+        >
+            { for props.todos.iter().map(|todo| html! {
+                <TodoItem key={todo.id.clone()} ..row_props(&props.rows, todo) />
+            })}
+        </ul>
+    }
+}
+
+pub fn row_props(rows: &TodoRowContext, todo: &Todo) -> TodoItemProps {
+    let is_current = |id: &Option<String>| id.as_ref() == Some(&todo.id);
+    TodoItemProps {
+        todo: todo.clone(),
+        now: rows.now,
+        utc_offset_ms: rows.utc_offset_ms,
+        projection: rows.projections.get(&todo.id).copied(),
+        categories: rows.categories.clone(),
+        manual_sort: rows.manual_sort,
+        is_editing: is_current(&rows.edit_id),
+        is_selected: rows.selected.contains(&todo.id),
+        is_focused: is_current(&rows.focused_id),
+        is_expanded: rows.expanded.contains(&todo.id),
+        is_dragging: is_current(&rows.dragging_id),
+        is_due_popover_open: is_current(&rows.due_popover),
+        subtask_error: rows
+            .subtask_error
+            .as_ref()
+            .filter(|(id, _)| *id == todo.id)
+            .map(|(_, error)| error.clone()),
+        edit: rows.edit.clone(),
+        callbacks: rows.callbacks.clone(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_derive_row_flags_for_matching_todo_only() {
+        let rows = TodoRowContext {
+            edit_id: Some("1".to_string()),
+            selected: HashSet::from(["2".to_string()]),
+            subtask_error: Some(("1".to_string(), "Subtask can't be empty".to_string())),
+            projections: HashMap::from([("2".to_string(), "Mon")]),
+            ..Default::default()
+        };
+        let todo = |id: &str| Todo {
+            id: id.to_string(),
+            ..Default::default()
+        };
+
+        let first = row_props(&rows, &todo("1"));
+        assert!(first.is_editing && !first.is_selected);
+        assert_eq!(first.subtask_error.as_deref(), Some("Subtask can't be empty"));
+        assert_eq!(first.projection, None);
+
+        let second = row_props(&rows, &todo("2"));
+        assert!(!second.is_editing && second.is_selected);
+        assert_eq!(second.subtask_error, None);
+        assert_eq!(second.projection, Some("Mon"));
+    }
+}
@@ -0,0 +1,253 @@
+use std::ops::Deref;
+use std::rc::Rc;
+
+use yew::Reducible;
+
+pub const MAX_HISTORY: usize = 50;
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct History<T> {
+    pub past: Vec<T>,
+    pub future: Vec<T>,
+}
+
+impl<T> Default for History<T> {
+    fn default() -> Self {
+        Self {
+            past: Vec::new(),
+            future: Vec::new(),
+        }
+    }
+}
+
+pub fn record<T: Clone>(past: &[T], present: &T) -> History<T> {
+    let skip = (past.len() + 1).saturating_sub(MAX_HISTORY);
+    let mut new_past: Vec<T> = past.iter().skip(skip).cloned().collect();
+    new_past.push(present.clone());
+    History {
+        past: new_past,
+        future: Vec::new(),
+    }
+}
+
+pub fn undo<T: Clone>(past: &[T], present: &T, future: &[T]) -> Option<(History<T>, T)> {
+    let (previous, rest) = past.split_last()?;
+    let mut new_future = future.to_vec();
+    new_future.push(present.clone());
+    Some((
+        History {
+            past: rest.to_vec(),
+            future: new_future,
+        },
+        previous.clone(),
+    ))
+}
+
+pub fn redo<T: Clone>(past: &[T], present: &T, future: &[T]) -> Option<(History<T>, T)> {
+    let (next, rest) = future.split_last()?;
+    let mut new_past = past.to_vec();
+    new_past.push(present.clone());
+    Some((
+        History {
+            past: new_past,
+            future: rest.to_vec(),
+        },
+        next.clone(),
+    ))
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct UndoableState<T> {
+    pub past: Vec<T>,
+    pub present: T,
+    pub future: Vec<T>,
+}
+
+impl<T> UndoableState<T> {
+    pub fn new(present: T) -> Self {
+        Self {
+            past: Vec::new(),
+            present,
+            future: Vec::new(),
+        }
+    }
+}
+
+impl<T> Deref for UndoableState<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.present
+    }
+}
+
+pub trait Apply: Sized {
+    type Action;
+
+    fn apply(&self, action: Self::Action) -> Option<Self>;
+}
+
+pub enum HistoryAction<T: Apply> {
+    Apply(T::Action),
+    Replace(T),
+    Reset(T),
+    Undo,
+    Redo,
+}
+
+impl<T: Apply + Clone> Reducible for UndoableState<T> {
+    type Action = HistoryAction<T>;
+
+    fn reduce(self: Rc<Self>, action: Self::Action) -> Rc<Self> {
+        let step = match action {
+            HistoryAction::Apply(action) => {
+                let Some(present) = self.present.apply(action) else {
+                    return self;
+                };
+                let History { past, future } = record(&self.past, &self.present);
+                return Rc::new(Self { past, present, future });
+            }
+            HistoryAction::Replace(present) => {
+                return Rc::new(Self {
+                    past: self.past.clone(),
+                    present,
+                    future: self.future.clone(),
+                });
+            }
+            HistoryAction::Reset(present) => return Rc::new(Self::new(present)),
+            HistoryAction::Undo => undo(&self.past, &self.present, &self.future),
+            HistoryAction::Redo => redo(&self.past, &self.present, &self.future),
+        };
+        match step {
+            Some((History { past, future }, present)) => Rc::new(Self { past, present, future }),
+            None => self,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_record_present_and_clear_future() {
+        let history = record(&[1, 2], &3);
+        assert_eq!(history.past, vec![1, 2, 3]);
+        assert!(history.future.is_empty());
+    }
+
+    #[test]
+    fn should_cap_history_length() {
+        let past: Vec<usize> = (0..MAX_HISTORY).collect();
+        let history = record(&past, &MAX_HISTORY);
+        assert_eq!(history.past.len(), MAX_HISTORY);
+        assert_eq!(history.past[0], 1);
+        assert_eq!(history.past[MAX_HISTORY - 1], MAX_HISTORY);
+    }
+
+    #[test]
+    fn should_undo_to_previous_state() {
+        let (history, present) = undo(&[1, 2], &3, &[]).unwrap();
+        assert_eq!(present, 2);
+        assert_eq!(history.past, vec![1]);
+        assert_eq!(history.future, vec![3]);
+    }
+
+    #[test]
+    fn should_not_undo_with_empty_past() {
+        assert!(undo(&[], &1, &[2]).is_none());
+    }
+
+    #[test]
+    fn should_redo_to_next_state() {
+        let (history, present) = redo(&[1], &2, &[4, 3]).unwrap();
+        assert_eq!(present, 3);
+        assert_eq!(history.past, vec![1, 2]);
+        assert_eq!(history.future, vec![4]);
+    }
+
+    #[test]
+    fn should_not_redo_with_empty_future() {
+        assert!(redo(&[1], &2, &[]).is_none());
+    }
+
+    #[test]
+    fn should_round_trip_undo_and_redo() {
+        let (history, present) = undo(&[1, 2], &3, &[]).unwrap();
+        let (history, present) = redo(&history.past, &present, &history.future).unwrap();
+        assert_eq!(present, 3);
+        assert_eq!(history.past, vec![1, 2]);
+        assert!(history.future.is_empty());
+    }
+
+    impl Apply for usize {
+        type Action = usize;
+
+        fn apply(&self, next: usize) -> Option<usize> {
+            (next != *self).then_some(next)
+        }
+    }
+
+    fn apply(state: UndoableState<usize>, actions: Vec<HistoryAction<usize>>) -> UndoableState<usize> {
+        let state = actions.into_iter().fold(Rc::new(state), |state, action| state.reduce(action));
+        (*state).clone()
+    }
+
+    #[test]
+    fn should_record_undo_and_redo_through_reducer() {
+        let state = apply(
+            UndoableState::new(1),
+            vec![HistoryAction::Apply(2), HistoryAction::Apply(3), HistoryAction::Undo],
+        );
+        assert_eq!(*state, 2);
+        assert_eq!(state.past, vec![1]);
+        assert_eq!(state.future, vec![3]);
+        let state = apply(state, vec![HistoryAction::Redo]);
+        assert_eq!(*state, 3);
+        assert_eq!(state.past, vec![1, 2]);
+        assert!(state.future.is_empty());
+    }
+
+    #[test]
+    fn should_not_record_rejected_actions() {
+        let state = apply(UndoableState::new(1), vec![HistoryAction::Apply(2), HistoryAction::Apply(2)]);
+        assert_eq!(*state, 2);
+        assert_eq!(state.past, vec![1]);
+    }
+
+    #[test]
+    fn should_ignore_undo_and_redo_at_history_edges() {
+        let state = UndoableState::new(1);
+        assert_eq!(apply(state.clone(), vec![HistoryAction::Undo]), state);
+        assert_eq!(apply(state.clone(), vec![HistoryAction::Redo]), state);
+    }
+
+    #[test]
+    fn should_replace_present_without_touching_history() {
+        let state = apply(
+            UndoableState::new(1),
+            vec![HistoryAction::Apply(2), HistoryAction::Undo, HistoryAction::Replace(5)],
+        );
+        assert_eq!(*state, 5);
+        assert!(state.past.is_empty());
+        assert_eq!(state.future, vec![2]);
+    }
+
+    #[test]
+    fn should_clear_history_on_reset() {
+        let state = apply(
+            UndoableState::new(1),
+            vec![HistoryAction::Apply(2), HistoryAction::Reset(7)],
+        );
+        assert_eq!(state, UndoableState::new(7));
+    }
+
+    #[test]
+    fn should_clear_redo_stack_on_new_edit_after_undo() {
+        let (history, present) = undo(&[1, 2], &3, &[]).unwrap();
+        assert_eq!(history.future, vec![3]);
+        let history = record(&history.past, &present);
+        assert_eq!(history.past, vec![1, 2]);
+        assert!(history.future.is_empty());
+    }
+}
@@ -1,366 +1,8089 @@
 use yew::prelude::*;
-use web_sys::HtmlInputElement;
+use yew_router::prelude::*;
+use wasm_bindgen::JsCast;
+use wasm_bindgen_futures::{spawn_local, JsFuture};
+use web_sys::{HtmlAnchorElement, HtmlInputElement, HtmlSelectElement};
 use serde::{Serialize, Deserialize};
-use gloo_storage::{LocalStorage, Storage};
+use gloo_events::EventListener;
+use gloo_storage::errors::StorageError;
+use gloo_storage::{LocalStorage, SessionStorage, Storage};
+use gloo_timers::callback::{Interval, Timeout};
+use pulldown_cmark::{Event as MdEvent, Options, Parser, Tag, TagEnd};
 use uuid::Uuid;
+use std::cell::{Cell, RefCell};
+use std::collections::{BTreeMap, HashMap, HashSet};
 
-const STORAGE_KEY: &str = "todos";
+mod backend;
+mod components;
+mod history;
+
+use components::error_banner::ErrorBanner;
+use components::todo_form::TodoForm;
+use components::todo_item::{EditState, TodoItemCallbacks};
+use components::todo_list::{TodoList, TodoRowContext};
+use history::{Apply, HistoryAction, UndoableState};
+
+thread_local! {
+    static PENDING_WRITE: RefCell<PendingWrite> = RefCell::new(PendingWrite::default());
+    static FALLBACK_STORE: RefCell<Option<MemoryStore>> = const { RefCell::new(None) };
+    static READ_REVISION: Cell<u64> = const { Cell::new(0) };
+    static SAVE_CONFLICT: Cell<bool> = const { Cell::new(false) };
+}
+
+const LEGACY_STORAGE_KEY: &str = "todos";
+const PROFILE_KEY: &str = "profile";
+const STORAGE_MODE_KEY: &str = "storage_mode";
+const REVISION_KEY_PREFIX: &str = "revision";
+const CONFLICT_MESSAGE: &str = "This list changed in another tab — reload?";
+const DEFAULT_PROFILE: &str = "default";
+const SETTINGS_KEY: &str = "settings";
+const FILTER_KEY: &str = "filter";
+const LAST_ROLLOVER_KEY: &str = "last_rollover_day";
+const DRAFT_KEY: &str = "draft";
+const SCROLL_KEY: &str = "scroll_top";
+const PLACEHOLDER_TITLE: &str = "Untitled task";
+const APP_TITLE: &str = "Todos";
+const ONE_MINUTE_MS: f64 = 60.0 * 1000.0;
+const ONE_HOUR_MS: f64 = 60.0 * ONE_MINUTE_MS;
+const ONE_DAY_MS: f64 = 24.0 * ONE_HOUR_MS;
+const SNOOZE_CHIP_THRESHOLD: u32 = 3;
+const MAX_TITLE_HISTORY: usize = 10;
+const RECENT_WINDOW_MS: f64 = 60.0 * 60.0 * 1000.0;
+const LOAD_RETRY_DELAY_MS: u32 = 500;
+const MAX_TITLE_LEN: usize = 200;
+const ANIMATE_REORDER: bool = true;
+const REORDER_ANIMATION_MS: u32 = 200;
+const SCROLL_SAVE_THROTTLE_MS: f64 = 200.0;
+const REMINDER_WINDOW_MS: f64 = 15.0 * ONE_MINUTE_MS;
+const REMINDER_CHECK_MS: u32 = 60_000;
+const EDITED_WINDOW_MS: f64 = ONE_MINUTE_MS;
+const PAGE_SIZE: usize = 20;
+const MAX_TODOS: usize = 500;
+const CONFIRM_THRESHOLD: usize = 1;
+const SCHEMA_VERSION: u32 = 1;
+const THROUGHPUT_WINDOW_DAYS: f64 = 7.0;
+const WEEKDAY_LABELS: [&str; 7] = ["Sun", "Mon", "Tue", "Wed", "Thu", "Fri", "Sat"];
+const TITLE_CASE_SMALL_WORDS: [&str; 12] =
+    ["a", "an", "and", "as", "at", "but", "for", "in", "of", "on", "or", "the"];
+const ESCALATE_TO_MEDIUM_MS: f64 = 3.0 * ONE_DAY_MS;
+const ESCALATE_TO_HIGH_MS: f64 = ONE_DAY_MS;
 
 const BUTTON_CLASS: &str = "px-2 py-1 rounded text-white";
 const SAVE_BUTTON: &str = "ml-2 bg-green-500 hover:bg-green-600";
 const CANCEL_BUTTON: &str = "ml-2 bg-gray-500 hover:bg-gray-600";
 const EDIT_BUTTON: &str = "ml-2 bg-yellow-500 hover:bg-yellow-600";
 const DELETE_BUTTON: &str = "ml-2 bg-red-500 hover:bg-red-600";
+const ARCHIVE_BUTTON: &str = "ml-2 bg-gray-500 hover:bg-gray-600";
+const DUPLICATE_BUTTON: &str = "ml-2 bg-teal-500 hover:bg-teal-600";
+const RESTORE_BUTTON: &str = "ml-2 bg-green-500 hover:bg-green-600";
+const SNOOZE_BUTTON: &str = "ml-2 bg-indigo-500 hover:bg-indigo-600";
+const NEUTRAL_BORDER_COLOR: &str = "#e5e7eb";
+const DEFAULT_CATEGORY_COLOR: &str = "#3b82f6";
+const MOVE_BUTTON: &str = "ml-1 px-1 text-gray-500 hover:text-gray-800";
 const ADD_BUTTON: &str = "bg-blue-500 hover:bg-blue-600 px-4 py-2 rounded";
 
-#[derive(Clone, PartialEq, Serialize, Deserialize)]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+enum Priority {
+    Low,
+    #[default]
+    Medium,
+    High,
+}
+
+impl Priority {
+    fn label(self) -> &'static str {
+        match self {
+            Priority::Low => "Low",
+            Priority::Medium => "Medium",
+            Priority::High => "High",
+        }
+    }
+
+    fn badge_class(self) -> &'static str {
+        match self {
+            Priority::Low => "bg-gray-200 text-gray-700",
+            Priority::Medium => "bg-yellow-200 text-yellow-800",
+            Priority::High => "bg-red-200 text-red-800",
+        }
+    }
+
+    fn next(self) -> Self {
+        match self {
+            Priority::Low => Priority::Medium,
+            Priority::Medium => Priority::High,
+            Priority::High => Priority::Low,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize)]
+enum Filter {
+    #[default]
+    All,
+    Active,
+    Completed,
+}
+
+impl Filter {
+    const ALL: [Filter; 3] = [Filter::All, Filter::Active, Filter::Completed];
+
+    fn label(self) -> &'static str {
+        match self {
+            Filter::All => "All",
+            Filter::Active => "Active",
+            Filter::Completed => "Completed",
+        }
+    }
+
+    fn matches(self, todo: &Todo) -> bool {
+        match self {
+            Filter::All => true,
+            Filter::Active => !todo.completed,
+            Filter::Completed => todo.completed,
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Routable)]
+enum Route {
+    #[at("/")]
+    Home,
+    #[at("/active")]
+    Active,
+    #[at("/completed")]
+    Completed,
+    #[at("/edit/:id")]
+    Edit { id: String },
+    #[not_found]
+    #[at("/404")]
+    NotFound,
+}
+
+fn filter_for_route(route: &Route) -> Filter {
+    match route {
+        Route::Active => Filter::Active,
+        Route::Completed => Filter::Completed,
+        Route::Home | Route::Edit { .. } | Route::NotFound => Filter::All,
+    }
+}
+
+fn resolve_edit_route(todos: &[Todo], route: &Route) -> Option<Result<String, String>> {
+    let Route::Edit { id } = route else {
+        return None;
+    };
+    Some(
+        todos
+            .iter()
+            .any(|todo| todo.id == *id && !todo.archived)
+            .then(|| id.clone())
+            .ok_or_else(|| format!("No todo found to edit ({})", id)),
+    )
+}
+
+fn route_for_filter(filter: Filter) -> Route {
+    match filter {
+        Filter::All => Route::Home,
+        Filter::Active => Route::Active,
+        Filter::Completed => Route::Completed,
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+enum SortMode {
+    #[default]
+    Manual,
+    Priority,
+    Starred,
+    Due,
+    Alphabetical,
+}
+
+impl SortMode {
+    const ALL: [SortMode; 5] = [
+        SortMode::Manual,
+        SortMode::Priority,
+        SortMode::Starred,
+        SortMode::Due,
+        SortMode::Alphabetical,
+    ];
+
+    fn value(self) -> &'static str {
+        match self {
+            SortMode::Manual => "manual",
+            SortMode::Priority => "priority",
+            SortMode::Starred => "starred",
+            SortMode::Due => "due",
+            SortMode::Alphabetical => "alphabetical",
+        }
+    }
+
+    fn from_value(value: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|mode| mode.value() == value)
+    }
+
+    fn label(self) -> &'static str {
+        match self {
+            SortMode::Manual => "Manual order",
+            SortMode::Priority => "Priority",
+            SortMode::Starred => "Starred first",
+            SortMode::Due => "Due date",
+            SortMode::Alphabetical => "Sort A–Z",
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+enum StorageMode {
+    #[default]
+    Local,
+    Session,
+}
+
+impl StorageMode {
+    const ALL: [StorageMode; 2] = [StorageMode::Local, StorageMode::Session];
+
+    fn value(self) -> &'static str {
+        match self {
+            StorageMode::Local => "local",
+            StorageMode::Session => "session",
+        }
+    }
+
+    fn from_value(value: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|mode| mode.value() == value)
+    }
+
+    fn label(self) -> &'static str {
+        match self {
+            StorageMode::Local => "Keep after closing",
+            StorageMode::Session => "This session only",
+        }
+    }
+}
+
+trait TodoStore {
+    fn load(&self, key: &str) -> Result<Vec<Todo>, StorageError>;
+    fn save(&self, key: &str, todos: &[Todo]) -> Result<(), StorageError>;
+    fn load_revision(&self, key: &str) -> u64;
+    fn save_revision(&self, key: &str, revision: u64) -> Result<(), StorageError>;
+}
+
+fn revision_key(key: &str) -> String {
+    format!("{}:{}", REVISION_KEY_PREFIX, key)
+}
+
+struct LocalStore;
+
+impl TodoStore for LocalStore {
+    fn load(&self, key: &str) -> Result<Vec<Todo>, StorageError> {
+        LocalStorage::get(key)
+    }
+
+    fn save(&self, key: &str, todos: &[Todo]) -> Result<(), StorageError> {
+        LocalStorage::set(key, todos)
+    }
+
+    fn load_revision(&self, key: &str) -> u64 {
+        LocalStorage::get(revision_key(key)).unwrap_or_default()
+    }
+
+    fn save_revision(&self, key: &str, revision: u64) -> Result<(), StorageError> {
+        LocalStorage::set(revision_key(key), revision)
+    }
+}
+
+struct SessionStore;
+
+impl TodoStore for SessionStore {
+    fn load(&self, key: &str) -> Result<Vec<Todo>, StorageError> {
+        SessionStorage::get(key)
+    }
+
+    fn save(&self, key: &str, todos: &[Todo]) -> Result<(), StorageError> {
+        SessionStorage::set(key, todos)
+    }
+
+    fn load_revision(&self, key: &str) -> u64 {
+        SessionStorage::get(revision_key(key)).unwrap_or_default()
+    }
+
+    fn save_revision(&self, key: &str, revision: u64) -> Result<(), StorageError> {
+        SessionStorage::set(revision_key(key), revision)
+    }
+}
+
+#[derive(Clone, Debug, Default)]
+struct MemoryStore {
+    todos: std::rc::Rc<RefCell<HashMap<String, Vec<Todo>>>>,
+    revisions: std::rc::Rc<RefCell<HashMap<String, u64>>>,
+}
+
+impl TodoStore for MemoryStore {
+    fn load(&self, key: &str) -> Result<Vec<Todo>, StorageError> {
+        self.todos
+            .borrow()
+            .get(key)
+            .cloned()
+            .ok_or_else(|| StorageError::KeyNotFound(key.to_string()))
+    }
+
+    fn save(&self, key: &str, todos: &[Todo]) -> Result<(), StorageError> {
+        self.todos.borrow_mut().insert(key.to_string(), todos.to_vec());
+        Ok(())
+    }
+
+    fn load_revision(&self, key: &str) -> u64 {
+        self.revisions.borrow().get(key).copied().unwrap_or_default()
+    }
+
+    fn save_revision(&self, key: &str, revision: u64) -> Result<(), StorageError> {
+        self.revisions.borrow_mut().insert(key.to_string(), revision);
+        Ok(())
+    }
+}
+
+fn next_revision(current: u64) -> u64 {
+    current.saturating_add(1)
+}
+
+fn has_conflict(stored: u64, last_read: u64) -> bool {
+    stored > last_read
+}
+
+fn save_conflict() -> bool {
+    SAVE_CONFLICT.with(Cell::get)
+}
+
+fn resolve_conflict() {
+    SAVE_CONFLICT.with(|conflict| conflict.set(false));
+}
+
+fn sync_read_revision(store: &dyn TodoStore, key: &str) {
+    READ_REVISION.with(|read| read.set(store.load_revision(key)));
+}
+
+fn save_with_revision(store: &dyn TodoStore, key: &str, todos: &[Todo]) -> Result<(), StorageError> {
+    let stored = store.load_revision(key);
+    store.save(key, todos)?;
+    let revision = next_revision(stored);
+    store.save_revision(key, revision)?;
+    READ_REVISION.with(|read| read.set(revision));
+    Ok(())
+}
+
+fn persistent_store() -> Box<dyn TodoStore> {
+    match load_storage_mode() {
+        StorageMode::Local => Box::new(LocalStore),
+        StorageMode::Session => Box::new(SessionStore),
+    }
+}
+
+fn todo_store() -> Box<dyn TodoStore> {
+    match fallback_store() {
+        Some(store) => Box::new(store),
+        None => persistent_store(),
+    }
+}
+
+fn fallback_store() -> Option<MemoryStore> {
+    FALLBACK_STORE.with(|fallback| fallback.borrow().clone())
+}
+
+fn enable_fallback_store(key: &str, todos: &[Todo]) {
+    let store = fallback_store().unwrap_or_default();
+    let _ = store.save(key, todos);
+    FALLBACK_STORE.with(|fallback| *fallback.borrow_mut() = Some(store));
+}
+
+fn clear_fallback_store() {
+    FALLBACK_STORE.with(|fallback| *fallback.borrow_mut() = None);
+}
+
+fn fallback_active() -> bool {
+    FALLBACK_STORE.with(|fallback| fallback.borrow().is_some())
+}
+
+fn track_fallback(result: &Result<(), StorageError>, key: &str, todos: &[Todo]) {
+    if result.is_err() {
+        enable_fallback_store(key, todos);
+    } else {
+        clear_fallback_store();
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+enum Recurrence {
+    Daily,
+    Weekly,
+    Monthly,
+}
+
+impl Recurrence {
+    const ALL: [Recurrence; 3] = [Recurrence::Daily, Recurrence::Weekly, Recurrence::Monthly];
+
+    fn value(self) -> &'static str {
+        match self {
+            Recurrence::Daily => "daily",
+            Recurrence::Weekly => "weekly",
+            Recurrence::Monthly => "monthly",
+        }
+    }
+
+    fn from_value(value: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|recurrence| recurrence.value() == value)
+    }
+
+    fn label(self) -> &'static str {
+        match self {
+            Recurrence::Daily => "Daily",
+            Recurrence::Weekly => "Weekly",
+            Recurrence::Monthly => "Monthly",
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum ReviewStep {
+    Keep,
+    Complete,
+    Delete,
+    Snooze,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum DueAction {
+    AddDay,
+    AddWeek,
+    Clear,
+    Pick(String),
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum LoadFailure {
+    Missing,
+    Corrupt,
+    Access,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+struct Settings {
+    allow_placeholder: bool,
+    capacity: Option<u32>,
+    sort_mode: SortMode,
+    sort_on_load: bool,
+    auto_capitalize: bool,
+    categories: Vec<Category>,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+struct Category {
+    name: String,
+    color: String,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+struct Subtask {
+    id: String,
+    title: String,
+    done: bool,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
 struct Todo {
     id: String,
     title: String,
     completed: bool,
+    #[serde(default)]
+    archived: bool,
+    #[serde(default)]
+    due_at: Option<f64>,
+    #[serde(default)]
+    snooze_count: u32,
+    #[serde(default)]
+    priority: Priority,
+    #[serde(default)]
+    title_history: Vec<(f64, String)>,
+    #[serde(default)]
+    completed_at: Option<f64>,
+    #[serde(default)]
+    starred: bool,
+    #[serde(default)]
+    pinned: bool,
+    #[serde(default)]
+    tags: Vec<String>,
+    #[serde(default)]
+    points: Option<u32>,
+    #[serde(default)]
+    require_review: bool,
+    #[serde(default)]
+    review_confirmed: bool,
+    #[serde(default)]
+    subtasks: Vec<Subtask>,
+    #[serde(default)]
+    blocked_reason: Option<String>,
+    #[serde(default)]
+    recurrence: Option<Recurrence>,
+    #[serde(default)]
+    created_at: Option<f64>,
+    #[serde(default)]
+    context: Option<String>,
+    #[serde(default)]
+    category: Option<Category>,
+    #[serde(default)]
+    updated_at: Option<f64>,
+    #[serde(default)]
+    estimate_minutes: Option<u32>,
+    #[serde(default)]
+    actual_minutes: Option<u32>,
+    #[serde(default)]
+    order: u32,
+}
+
+type TodosHandle = UseReducerHandle<UndoableState<Vec<Todo>>>;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum ExportFormat {
+    Json,
+    Csv,
+    Markdown,
+}
+
+impl ExportFormat {
+    const ALL: [ExportFormat; 3] = [ExportFormat::Json, ExportFormat::Csv, ExportFormat::Markdown];
+
+    fn value(self) -> &'static str {
+        match self {
+            ExportFormat::Json => "json",
+            ExportFormat::Csv => "csv",
+            ExportFormat::Markdown => "md",
+        }
+    }
+
+    fn from_value(value: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|format| format.value() == value)
+    }
+
+    fn label(self) -> &'static str {
+        match self {
+            ExportFormat::Json => "JSON",
+            ExportFormat::Csv => "CSV",
+            ExportFormat::Markdown => "Markdown",
+        }
+    }
+
+    fn mime_type(self) -> &'static str {
+        match self {
+            ExportFormat::Json => "application/json",
+            ExportFormat::Csv => "text/csv",
+            ExportFormat::Markdown => "text/markdown",
+        }
+    }
 }
 
-fn create_new_todo(todos: &[Todo], title: String) -> Vec<Todo> {
+fn create_new_todo(todos: &[Todo], title: String, auto_capitalize: bool, now: f64) -> Vec<Todo> {
+    let (title, context) = extract_context(&title);
+    let (title, tags) = extract_tags(&title);
+    let title = if auto_capitalize { to_title_case(&title) } else { title };
     let mut new_todos = Vec::with_capacity(todos.len() + 1);
     new_todos.extend(todos.iter().cloned());
     new_todos.push(Todo {
         id: Uuid::new_v4().to_string(),
         title,
         completed: false,
+        tags,
+        context,
+        created_at: Some(now),
+        order: next_order(todos),
+        ..Default::default()
     });
     new_todos
 }
 
+fn next_order(todos: &[Todo]) -> u32 {
+    todos.iter().map(|todo| todo.order).max().map_or(0, |max| max + 1)
+}
+
+fn renumber(todos: Vec<Todo>) -> Vec<Todo> {
+    todos
+        .into_iter()
+        .zip(0..)
+        .map(|(todo, order)| Todo { order, ..todo })
+        .collect()
+}
+
+fn sort_by_order(todos: &[Todo]) -> Vec<Todo> {
+    let mut sorted = todos.to_vec();
+    sorted.sort_by_key(|todo| todo.order);
+    sorted
+}
+
+fn migrate_order(todos: Vec<Todo>) -> Vec<Todo> {
+    let orders: HashSet<u32> = todos.iter().map(|todo| todo.order).collect();
+    if orders.len() == todos.len() {
+        todos
+    } else {
+        renumber(todos)
+    }
+}
+
+fn try_create_new_todo(
+    todos: &[Todo],
+    title: String,
+    auto_capitalize: bool,
+    now: f64,
+) -> Result<Vec<Todo>, String> {
+    if todos.len() >= MAX_TODOS {
+        return Err(format!("Limit reached ({} tasks)", MAX_TODOS));
+    }
+    Ok(create_new_todo(todos, title, auto_capitalize, now))
+}
+
+fn to_title_case(s: &str) -> String {
+    s.split_whitespace()
+        .enumerate()
+        .map(|(i, word)| {
+            let lower = word.to_lowercase();
+            if i > 0 && TITLE_CASE_SMALL_WORDS.contains(&lower.as_str()) {
+                return lower;
+            }
+            let mut chars = word.chars();
+            chars.next().map_or_else(String::new, |first| {
+                first.to_uppercase().chain(chars).collect()
+            })
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+fn extract_context(raw: &str) -> (String, Option<String>) {
+    if let Some(rest) = raw.trim_start().strip_prefix('@') {
+        let (token, remainder) = rest.split_once(char::is_whitespace).unwrap_or((rest, ""));
+        let remainder = remainder.trim();
+        if !token.is_empty() && !remainder.is_empty() {
+            return (remainder.to_string(), Some(token.to_string()));
+        }
+    }
+    (raw.to_string(), None)
+}
+
+fn is_tag_name(tag: &str) -> bool {
+    let mut chars = tag.chars();
+    chars.next().is_some_and(|first| first.is_ascii_alphabetic())
+        && chars.all(|c| c.is_alphanumeric() || c == '_')
+}
+
+fn extract_tags(raw: &str) -> (String, Vec<String>) {
+    let mut words = Vec::new();
+    let mut tags: Vec<String> = Vec::new();
+    for word in raw.split_whitespace() {
+        match word.strip_prefix('#') {
+            Some(tag) if is_tag_name(tag) => {
+                if !tags.iter().any(|existing| existing == tag) {
+                    tags.push(tag.to_string());
+                }
+            }
+            _ => words.push(word),
+        }
+    }
+    if words.is_empty() {
+        (raw.trim().to_string(), Vec::new())
+    } else {
+        (words.join(" "), tags)
+    }
+}
+
+fn title_length(title: &str) -> usize {
+    title.chars().count()
+}
+
 fn is_valid_title(title: &str) -> bool {
-    !title.is_empty()
+    !title.trim().is_empty() && title_length(title) <= MAX_TITLE_LEN
+}
+
+fn validate_edit(todos: &[Todo], id: &str, title: &str) -> Result<(), String> {
+    let title = title.trim();
+    if title.is_empty() {
+        return Err("Title can't be empty".to_string());
+    }
+    if !is_valid_title(title) {
+        return Err(format!("Title must be at most {} characters", MAX_TITLE_LEN));
+    }
+    let title = title.to_lowercase();
+    let duplicate = todos.iter().any(|todo| {
+        todo.id != id && !todo.archived && todo.title.trim().to_lowercase() == title
+    });
+    if duplicate {
+        return Err("Another todo already has this title".to_string());
+    }
+    Ok(())
+}
+
+fn render_char_counter(len: usize) -> Html {
+    html! {
+        <span class={classes!("text-xs", "self-center", if len > MAX_TITLE_LEN { "text-red-500" } else { "text-gray-400" })}>
+            { format!("{}/{}", len, MAX_TITLE_LEN) }
+        </span>
+    }
+}
+
+fn read_input_title(input: &HtmlInputElement) -> String {
+    input.value().trim().to_string()
+}
+
+fn resolve_submit_title(raw: &str, allow_placeholder: bool) -> Option<String> {
+    let title = raw.trim();
+    if is_valid_title(title) {
+        Some(title.to_string())
+    } else if allow_placeholder {
+        Some(PLACEHOLDER_TITLE.to_string())
+    } else {
+        None
+    }
+}
+
+fn export_todos_json(todos: &[Todo]) -> String {
+    serde_json::to_string_pretty(todos).unwrap_or_default()
+}
+
+fn escape_csv_field(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+fn export_todos_csv(todos: &[Todo]) -> String {
+    let mut csv = String::from("id,title,completed\r\n");
+    for todo in todos {
+        csv.push_str(&format!(
+            "{},{},{}\r\n",
+            escape_csv_field(&todo.id),
+            escape_csv_field(&todo.title),
+            todo.completed
+        ));
+    }
+    csv
+}
+
+#[derive(Clone, Debug, PartialEq)]
+struct TagStat {
+    tag: String,
+    total: usize,
+    completed: usize,
+    percent: f64,
+}
+
+fn tag_report(todos: &[Todo]) -> Vec<TagStat> {
+    let mut counts: BTreeMap<&str, (usize, usize)> = BTreeMap::new();
+    for todo in todos.iter().filter(|todo| !todo.archived) {
+        for tag in &todo.tags {
+            let (total, completed) = counts.entry(tag).or_default();
+            *total += 1;
+            if todo.completed {
+                *completed += 1;
+            }
+        }
+    }
+    counts
+        .into_iter()
+        .map(|(tag, (total, completed))| TagStat {
+            tag: tag.to_string(),
+            total,
+            completed,
+            percent: completed as f64 * 100.0 / total as f64,
+        })
+        .collect()
+}
+
+fn export_tag_report_csv(stats: &[TagStat]) -> String {
+    let mut csv = String::from("tag,total,completed,percent\r\n");
+    for stat in stats {
+        csv.push_str(&format!(
+            "{},{},{},{:.1}\r\n",
+            escape_csv_field(&stat.tag),
+            stat.total,
+            stat.completed,
+            stat.percent
+        ));
+    }
+    csv
+}
+
+fn export_todos_markdown(todos: &[Todo]) -> String {
+    todos
+        .iter()
+        .map(|todo| format!("- [{}] {}\n", if todo.completed { "x" } else { " " }, todo.title))
+        .collect()
+}
+
+fn todos_to_text(todos: &[Todo]) -> String {
+    todos
+        .iter()
+        .map(|todo| format!("[{}] {}", if todo.completed { "x" } else { " " }, todo.title))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+fn contains_markup(s: &str) -> bool {
+    s.match_indices('<').any(|(index, _)| {
+        s[index + 1..]
+            .chars()
+            .next()
+            .is_some_and(|c| c.is_ascii_alphabetic() || matches!(c, '/' | '!' | '?'))
+    })
 }
 
-fn read_input_title(input: &HtmlInputElement) -> String {
-    input.value().trim().to_string()
-}
+fn validate_import(todos: Vec<Todo>) -> Result<Vec<Todo>, String> {
+    match todos.iter().find(|todo| {
+        contains_markup(&todo.title) || todo.tags.iter().any(|tag| contains_markup(tag))
+    }) {
+        Some(todo) => Err(format!("todo \"{}\" contains markup", todo.title)),
+        None => Ok(todos),
+    }
+}
+
+fn parse_todos_json(input: &str) -> Result<Vec<Todo>, String> {
+    serde_json::from_str(input)
+        .map_err(|e| e.to_string())
+        .and_then(validate_import)
+}
+
+fn parse_csv_records(input: &str) -> Result<Vec<Vec<String>>, String> {
+    let mut records = Vec::new();
+    let mut record = Vec::new();
+    let mut field = String::new();
+    let mut in_quotes = false;
+    let mut chars = input.chars().peekable();
+    while let Some(c) = chars.next() {
+        if in_quotes {
+            match c {
+                '"' if chars.peek() == Some(&'"') => {
+                    chars.next();
+                    field.push('"');
+                }
+                '"' => in_quotes = false,
+                _ => field.push(c),
+            }
+            continue;
+        }
+        match c {
+            '"' if field.is_empty() => in_quotes = true,
+            '"' => return Err(format!("unexpected quote in row {}", records.len() + 1)),
+            ',' => record.push(std::mem::take(&mut field)),
+            '\r' if chars.peek() == Some(&'\n') => {}
+            '\n' => {
+                record.push(std::mem::take(&mut field));
+                records.push(std::mem::take(&mut record));
+            }
+            _ => field.push(c),
+        }
+    }
+    if in_quotes {
+        return Err("unterminated quoted field".to_string());
+    }
+    if !field.is_empty() || !record.is_empty() {
+        record.push(field);
+        records.push(record);
+    }
+    Ok(records)
+}
+
+fn parse_todos_csv(input: &str) -> Result<Vec<Todo>, String> {
+    let mut records = parse_csv_records(input)?.into_iter();
+    let header = records.next().ok_or_else(|| "missing header row".to_string())?;
+    let column = |name: &str| header.iter().position(|field| field.trim() == name);
+    let title_column = column("title").ok_or_else(|| "missing title column".to_string())?;
+    let id_column = column("id");
+    let completed_column = column("completed");
+    let todos = records
+        .enumerate()
+        .filter(|(_, record)| record.iter().any(|field| !field.is_empty()))
+        .map(|(index, record)| {
+            let row = index + 2;
+            if record.len() != header.len() {
+                return Err(format!(
+                    "row {} has {} fields, expected {}",
+                    row,
+                    record.len(),
+                    header.len()
+                ));
+            }
+            let completed = match completed_column.map(|i| record[i].trim()) {
+                None | Some("") | Some("false") => false,
+                Some("true") => true,
+                Some(other) => return Err(format!("row {} has invalid completed value \"{}\"", row, other)),
+            };
+            let id = id_column
+                .map(|i| record[i].trim().to_string())
+                .filter(|id| !id.is_empty())
+                .unwrap_or_else(|| Uuid::new_v4().to_string());
+            Ok(Todo {
+                id,
+                title: record[title_column].clone(),
+                completed,
+                ..Default::default()
+            })
+        })
+        .collect::<Result<Vec<Todo>, String>>()?;
+    validate_import(todos)
+}
+
+fn parse_import(filename: &str, text: &str) -> Result<Vec<Todo>, String> {
+    if filename.to_ascii_lowercase().ends_with(".csv") {
+        parse_todos_csv(text)
+    } else {
+        parse_todos_json(text)
+    }
+    .map(migrate_order)
+}
+
+fn differs(a: &[Todo], b: &[Todo]) -> bool {
+    a != b
+}
+
+fn render_export(todos: &[Todo], format: ExportFormat) -> String {
+    match format {
+        ExportFormat::Json => export_todos_json(todos),
+        ExportFormat::Csv => export_todos_csv(todos),
+        ExportFormat::Markdown => export_todos_markdown(todos),
+    }
+}
+
+fn download_file(filename: &str, contents: &str, mime_type: &str) -> Result<(), String> {
+    let document = web_sys::window()
+        .and_then(|window| window.document())
+        .ok_or("No document available")?;
+    let options = web_sys::BlobPropertyBag::new();
+    options.set_type(mime_type);
+    let parts = js_sys::Array::of1(&contents.into());
+    let blob = web_sys::Blob::new_with_str_sequence_and_options(&parts, &options)
+        .map_err(|e| format!("{:?}", e))?;
+    let url = web_sys::Url::create_object_url_with_blob(&blob).map_err(|e| format!("{:?}", e))?;
+    let anchor = document
+        .create_element("a")
+        .map_err(|e| format!("{:?}", e))?
+        .unchecked_into::<HtmlAnchorElement>();
+    anchor.set_href(&url);
+    anchor.set_download(filename);
+    anchor.click();
+    web_sys::Url::revoke_object_url(&url).map_err(|e| format!("{:?}", e))
+}
+
+fn download_export(todos: &[Todo], format: ExportFormat, error_handle: &UseStateHandle<Option<String>>) {
+    let contents = render_export(todos, format);
+    let filename = format!("todos.{}", format.value());
+    if let Err(e) = download_file(&filename, &contents, format.mime_type()) {
+        error_handle.set(Some(format!("Export error: {}", e)));
+    }
+}
+
+fn copy_to_clipboard(text: String, error_handle: &UseStateHandle<Option<String>>) {
+    let Some(window) = web_sys::window() else {
+        error_handle.set(Some("Clipboard error: no window available".to_string()));
+        return;
+    };
+    let promise = window.navigator().clipboard().write_text(&text);
+    let error_handle = error_handle.clone();
+    spawn_local(async move {
+        if let Err(e) = JsFuture::from(promise).await {
+            error_handle.set(Some(format!("Clipboard error: {:?}", e)));
+        }
+    });
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+struct PendingWrite {
+    failed: Option<(String, Vec<Todo>)>,
+}
+
+impl PendingWrite {
+    fn is_pending(&self) -> bool {
+        self.failed.is_some()
+    }
+
+    fn pending_key(&self) -> Option<&str> {
+        self.failed.as_ref().map(|(key, _)| key.as_str())
+    }
+
+    fn attempt<E>(
+        &mut self,
+        key: &str,
+        todos: &[Todo],
+        save: impl FnOnce(&str, &[Todo]) -> Result<(), E>,
+    ) -> Result<(), E> {
+        let result = save(key, todos);
+        self.failed = result.is_err().then(|| (key.to_string(), todos.to_vec()));
+        result
+    }
+
+    fn retry<E>(&mut self, save: impl FnOnce(&str, &[Todo]) -> Result<(), E>) -> Result<(), E> {
+        match self.failed.take() {
+            Some((key, todos)) => self.attempt(&key, &todos, save),
+            None => Ok(()),
+        }
+    }
+}
+
+fn report_save_result(result: Result<(), StorageError>, error_handle: &UseStateHandle<Option<String>>) {
+    if let Err(e) = result {
+        error_handle.set(Some(format!("Storage error: {:?}", e)));
+    } else {
+        error_handle.set(None);
+    }
+}
+
+fn reject_conflicting_save(
+    store: &dyn TodoStore,
+    key: &str,
+    error_handle: &UseStateHandle<Option<String>>,
+) -> bool {
+    let last_read = READ_REVISION.with(Cell::get);
+    if save_conflict() || has_conflict(store.load_revision(key), last_read) {
+        SAVE_CONFLICT.with(|conflict| conflict.set(true));
+        error_handle.set(Some(CONFLICT_MESSAGE.to_string()));
+        return true;
+    }
+    false
+}
+
+fn save_todos_to_storage_with_error(
+    key: &str,
+    todos: &[Todo],
+    error_handle: &UseStateHandle<Option<String>>,
+) -> bool {
+    let store = persistent_store();
+    if reject_conflicting_save(store.as_ref(), key, error_handle) {
+        return false;
+    }
+    let result = PENDING_WRITE.with(|pending| {
+        pending
+            .borrow_mut()
+            .attempt(key, todos, |key, todos| save_with_revision(store.as_ref(), key, todos))
+    });
+    track_fallback(&result, key, todos);
+    let saved = result.is_ok();
+    report_save_result(result, error_handle);
+    saved
+}
+
+fn retry_pending_write(error_handle: &UseStateHandle<Option<String>>) {
+    let store = persistent_store();
+    let pending_key = PENDING_WRITE.with(|pending| pending.borrow().pending_key().map(str::to_string));
+    if let Some(key) = pending_key {
+        if reject_conflicting_save(store.as_ref(), &key, error_handle) {
+            return;
+        }
+    }
+    let result = PENDING_WRITE.with(|pending| {
+        pending.borrow_mut().retry(|key, todos| {
+            let result = save_with_revision(store.as_ref(), key, todos);
+            track_fallback(&result, key, todos);
+            result
+        })
+    });
+    report_save_result(result, error_handle);
+}
+
+fn decode_filter(stored: Option<&str>) -> Filter {
+    stored
+        .and_then(|raw| serde_json::from_str(raw).ok())
+        .unwrap_or_default()
+}
+
+fn load_filter() -> Filter {
+    decode_filter(LocalStorage::raw().get_item(FILTER_KEY).ok().flatten().as_deref())
+}
+
+fn save_filter(filter: Filter, error_handle: &UseStateHandle<Option<String>>) {
+    if let Err(e) = LocalStorage::set(FILTER_KEY, filter) {
+        error_handle.set(Some(format!("Storage error: {:?}", e)));
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+struct Diagnostics {
+    item_count: usize,
+    archived_count: usize,
+    serialized_bytes: usize,
+    schema_version: u32,
+    app_version: &'static str,
+}
+
+fn storage_available() -> bool {
+    web_sys::window()
+        .and_then(|window| window.local_storage().ok().flatten())
+        .is_some()
+}
+
+fn storage_bytes(todos: &[Todo]) -> usize {
+    serde_json::to_string(todos).map_or(0, |json| json.len())
+}
+
+fn collect_diagnostics(todos: &[Todo]) -> Diagnostics {
+    Diagnostics {
+        item_count: todos.len(),
+        archived_count: todos.iter().filter(|todo| todo.archived).count(),
+        serialized_bytes: storage_bytes(todos),
+        schema_version: SCHEMA_VERSION,
+        app_version: env!("CARGO_PKG_VERSION"),
+    }
+}
+
+fn is_diag_query(search: &str) -> bool {
+    search
+        .trim_start_matches('?')
+        .split('&')
+        .any(|pair| pair == "diag=1")
+}
+
+fn diag_requested() -> bool {
+    web_sys::window()
+        .and_then(|window| window.location().search().ok())
+        .is_some_and(|search| is_diag_query(&search))
+}
+
+fn storage_key_for(profile: &str) -> String {
+    format!("{}:{}", LEGACY_STORAGE_KEY, profile)
+}
+
+fn profile_from_key(key: &str) -> Option<&str> {
+    key.strip_prefix(LEGACY_STORAGE_KEY)?
+        .strip_prefix(':')
+        .filter(|profile| !profile.is_empty())
+}
+
+fn normalize_profile(name: &str) -> Option<String> {
+    let name = name.trim();
+    (!name.is_empty()).then(|| name.to_string())
+}
+
+fn profiles_from_keys(keys: impl IntoIterator<Item = String>) -> Vec<String> {
+    let mut profiles: Vec<String> = keys
+        .into_iter()
+        .filter_map(|key| profile_from_key(&key).map(str::to_string))
+        .chain(std::iter::once(DEFAULT_PROFILE.to_string()))
+        .collect();
+    profiles.sort();
+    profiles.dedup();
+    profiles
+}
+
+fn list_profiles(current: &str) -> Vec<String> {
+    let storage = LocalStorage::raw();
+    let len = storage.length().unwrap_or(0);
+    profiles_from_keys(
+        (0..len)
+            .filter_map(|index| storage.key(index).ok().flatten())
+            .chain(std::iter::once(storage_key_for(current))),
+    )
+}
+
+fn load_profile() -> String {
+    LocalStorage::get(PROFILE_KEY).unwrap_or_else(|_| DEFAULT_PROFILE.to_string())
+}
+
+fn save_profile(profile: &str, error_handle: &UseStateHandle<Option<String>>) {
+    if let Err(e) = LocalStorage::set(PROFILE_KEY, profile) {
+        error_handle.set(Some(format!("Storage error: {:?}", e)));
+    }
+}
+
+fn load_storage_mode() -> StorageMode {
+    LocalStorage::get(STORAGE_MODE_KEY).unwrap_or_default()
+}
+
+fn save_storage_mode(mode: StorageMode, error_handle: &UseStateHandle<Option<String>>) {
+    if let Err(e) = LocalStorage::set(STORAGE_MODE_KEY, mode) {
+        error_handle.set(Some(format!("Storage error: {:?}", e)));
+    }
+}
+
+fn todos_key() -> String {
+    storage_key_for(&load_profile())
+}
+
+fn migrate_legacy_todos() {
+    let storage = LocalStorage::raw();
+    let default_key = storage_key_for(DEFAULT_PROFILE);
+    if storage.get_item(&default_key).ok().flatten().is_some() {
+        return;
+    }
+    if let Some(legacy) = storage.get_item(LEGACY_STORAGE_KEY).ok().flatten() {
+        if storage.set_item(&default_key, &legacy).is_ok() {
+            let _ = storage.remove_item(LEGACY_STORAGE_KEY);
+        }
+    }
+}
+
+fn decode_draft(stored: Option<&str>) -> String {
+    stored.unwrap_or_default().to_string()
+}
+
+fn load_draft() -> String {
+    decode_draft(LocalStorage::raw().get_item(DRAFT_KEY).ok().flatten().as_deref())
+}
+
+fn save_draft(draft: &str) {
+    let storage = LocalStorage::raw();
+    let result = if draft.is_empty() {
+        storage.remove_item(DRAFT_KEY)
+    } else {
+        storage.set_item(DRAFT_KEY, draft)
+    };
+    if result.is_err() {
+        web_sys::console::log_1(&"Failed to save draft".into());
+    }
+}
+
+fn clamp_scroll(saved: i32, scroll_height: i32, client_height: i32) -> i32 {
+    saved.clamp(0, (scroll_height - client_height).max(0))
+}
+
+fn classify_load_error(error: &StorageError) -> LoadFailure {
+    match error {
+        StorageError::KeyNotFound(_) => LoadFailure::Missing,
+        StorageError::SerdeError(_) => LoadFailure::Corrupt,
+        StorageError::JsError(_) => LoadFailure::Access,
+    }
+}
+
+fn should_retry_load(failure: LoadFailure) -> bool {
+    failure == LoadFailure::Access
+}
+
+fn load_todos(key: &str) -> Result<Vec<Todo>, StorageError> {
+    load_todos_from(todo_store().as_ref(), key)
+}
+
+fn load_todos_from(store: &dyn TodoStore, key: &str) -> Result<Vec<Todo>, StorageError> {
+    sync_read_revision(store, key);
+    match store.load(key) {
+        Err(e) if classify_load_error(&e) == LoadFailure::Missing => Ok(Vec::new()),
+        result => result.map(migrate_order),
+    }
+}
+
+fn retry_load_after_delay(
+    todos_handle: &TodosHandle,
+    load_snapshot: &std::rc::Rc<std::cell::RefCell<Vec<Todo>>>,
+    loaded_handle: &UseStateHandle<bool>,
+    error_handle: &UseStateHandle<Option<String>>,
+) -> Timeout {
+    let todos_handle = todos_handle.clone();
+    let load_snapshot = load_snapshot.clone();
+    let loaded_handle = loaded_handle.clone();
+    let error_handle = error_handle.clone();
+    Timeout::new(LOAD_RETRY_DELAY_MS, move || match load_todos(&todos_key()) {
+        Ok(todos) => {
+            *load_snapshot.borrow_mut() = todos.clone();
+            update_todos_state(&todos_handle, todos);
+            loaded_handle.set(true);
+            error_handle.set(None);
+        }
+        Err(e) => error_handle.set(Some(format!("Failed to load todos: {:?}", e))),
+    })
+}
+
+fn load_settings() -> Settings {
+    LocalStorage::get(SETTINGS_KEY).unwrap_or_default()
+}
+
+fn save_settings(settings: &Settings, error_handle: &UseStateHandle<Option<String>>) {
+    if let Err(e) = LocalStorage::set(SETTINGS_KEY, settings) {
+        error_handle.set(Some(format!("Storage error: {:?}", e)));
+    }
+}
+
+fn update_settings(
+    settings_handle: &UseStateHandle<Settings>,
+    new_settings: Settings,
+    error_handle: &UseStateHandle<Option<String>>,
+) {
+    save_settings(&new_settings, error_handle);
+    settings_handle.set(new_settings);
+}
+
+fn update_todos_state(todos_handle: &TodosHandle, new_todos: Vec<Todo>) {
+    todos_handle.dispatch(HistoryAction::Replace(new_todos));
+}
+
+fn reset_todos_state(todos_handle: &TodosHandle, new_todos: Vec<Todo>) {
+    todos_handle.dispatch(HistoryAction::Reset(new_todos));
+}
+
+fn pending_ops_key() -> String {
+    backend::pending_ops_key(&load_profile())
+}
+
+fn flush_pending_ops(error_handle: &UseStateHandle<Option<String>>) {
+    let Some(base) = backend::BACKEND_URL else {
+        return;
+    };
+    let key = pending_ops_key();
+    let error_handle = error_handle.clone();
+    spawn_local(async move {
+        if let Err(e) = backend::flush_pending_ops(base, &key).await {
+            error_handle.set(Some(format!(
+                "Sync error: {} ({} changes queued)",
+                e,
+                backend::load_pending_ops(&key).len()
+            )));
+        }
+    });
+}
+
+fn sync_changes(ops: Vec<backend::Op>, error_handle: &UseStateHandle<Option<String>>) {
+    if backend::BACKEND_URL.is_none() || ops.is_empty() {
+        return;
+    }
+    if let Err(e) = backend::enqueue_ops(&pending_ops_key(), ops) {
+        error_handle.set(Some(format!("Storage error: {}", e)));
+        return;
+    }
+    flush_pending_ops(error_handle);
+}
+
+fn stamp_updated(old: &[Todo], new: &[Todo], now: f64) -> Vec<Todo> {
+    new.iter()
+        .map(|todo| match old.iter().find(|existing| existing.id == todo.id) {
+            Some(existing) if existing != todo => Todo {
+                updated_at: Some(now),
+                ..todo.clone()
+            },
+            _ => todo.clone(),
+        })
+        .collect()
+}
+
+fn was_recently_edited(todo: &Todo, now: f64, window_ms: f64) -> bool {
+    todo.updated_at.is_some_and(|updated_at| now - updated_at < window_ms)
+}
+
+fn ms_until_edit_badge_expires(todos: &[Todo], now: f64, window_ms: f64) -> Option<f64> {
+    todos
+        .iter()
+        .filter(|todo| was_recently_edited(todo, now, window_ms))
+        .filter_map(|todo| todo.updated_at)
+        .map(|updated_at| updated_at + window_ms - now)
+        .min_by(f64::total_cmp)
+}
+
+fn persist_todos(old_todos: &[Todo], new_todos: &[Todo], error_handle: &UseStateHandle<Option<String>>) -> bool {
+    let saved = save_todos_to_storage_with_error(&todos_key(), new_todos, error_handle);
+    sync_changes(backend::diff_todos(old_todos, new_todos), error_handle);
+    saved
+}
+
+fn with_snapshot<T>(todos: &[Todo], mutate: impl FnOnce(&[Todo]) -> T) -> (T, Vec<Todo>) {
+    (mutate(todos), todos.to_vec())
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum TodoAction {
+    Add {
+        title: String,
+        auto_capitalize: bool,
+        due_at: Option<f64>,
+        now: f64,
+    },
+    Delete { id: String },
+    Toggle {
+        id: String,
+        now: f64,
+        utc_offset_ms: f64,
+    },
+    UpdateTitle {
+        id: String,
+        title: String,
+        now: f64,
+    },
+    Star { id: String },
+    Pin { id: String },
+    Archive { id: String },
+    Restore { id: String },
+    Duplicate { id: String, now: f64 },
+    MoveUp { id: String, visible: Vec<String> },
+    MoveDown { id: String, visible: Vec<String> },
+    Reorder { from: usize, to: usize },
+    MoveToEdge { ids: HashSet<String>, to_top: bool },
+    SetMinutes {
+        id: String,
+        estimate_minutes: Option<u32>,
+        actual_minutes: Option<u32>,
+    },
+    SetPoints { id: String, points: Option<u32> },
+    SetPriority { id: String, priority: Priority },
+    SetRecurrence { id: String, recurrence: Option<Recurrence> },
+    SetCategory { id: String, category: Option<Category> },
+    SetBlockedReason { id: String, reason: String },
+    Snooze { id: String },
+    Due {
+        id: String,
+        action: DueAction,
+        now: f64,
+        utc_offset_ms: f64,
+    },
+    ToggleReview { id: String },
+    ConfirmReview {
+        id: String,
+        now: f64,
+        utc_offset_ms: f64,
+    },
+    AddSubtask { id: String, title: String },
+    ToggleSubtask {
+        id: String,
+        subtask_id: String,
+        now: f64,
+        utc_offset_ms: f64,
+    },
+    DeleteSubtask { id: String, subtask_id: String },
+    CompleteVisible {
+        ids: HashSet<String>,
+        now: f64,
+        utc_offset_ms: f64,
+    },
+    DeleteMany { ids: HashSet<String> },
+    ClearTags { ids: HashSet<String> },
+    RolloverOverdue { now: f64, utc_offset_ms: f64 },
+    Dedupe,
+    ClearCompleted,
+    ClearAll,
+    Replace { todos: Vec<Todo> },
+    Batch(Vec<TodoAction>),
+}
+
+fn reduce(todos: &[Todo], action: TodoAction) -> Result<Vec<Todo>, String> {
+    Ok(match action {
+        TodoAction::Add {
+            title,
+            auto_capitalize,
+            due_at,
+            now,
+        } => {
+            let created = try_create_new_todo(todos, title, auto_capitalize, now)?;
+            match (new_todo_id(todos, &created), due_at) {
+                (Some(id), Some(due_at)) => set_due(&created, &id, Some(due_at)),
+                _ => created,
+            }
+        }
+        TodoAction::Delete { id } => delete_todo(todos, &id),
+        TodoAction::Toggle { id, now, utc_offset_ms } => {
+            spawn_recurrences(todos, &toggle_todo(todos, &id, now), now, utc_offset_ms)
+        }
+        TodoAction::UpdateTitle { id, title, now } => update_todo_title(todos, &id, &title, now),
+        TodoAction::Star { id } => toggle_star(todos, &id),
+        TodoAction::Pin { id } => toggle_pin(todos, &id),
+        TodoAction::Archive { id } => archive_todo(todos, &id),
+        TodoAction::Restore { id } => restore_todo(todos, &id),
+        TodoAction::Duplicate { id, now } => duplicate_todo(todos, &id, now),
+        TodoAction::MoveUp { id, visible } => move_todo_up(todos, &id, &visible),
+        TodoAction::MoveDown { id, visible } => move_todo_down(todos, &id, &visible),
+        TodoAction::Reorder { from, to } => reorder(todos, from, to),
+        TodoAction::MoveToEdge { ids, to_top } => move_selection_to_edge(todos, &ids, to_top),
+        TodoAction::SetMinutes {
+            id,
+            estimate_minutes,
+            actual_minutes,
+        } => set_actual_minutes(&set_estimate_minutes(todos, &id, estimate_minutes), &id, actual_minutes),
+        TodoAction::SetPoints { id, points } => set_points(todos, &id, points),
+        TodoAction::SetPriority { id, priority } => set_priority(todos, &id, priority),
+        TodoAction::SetRecurrence { id, recurrence } => set_recurrence(todos, &id, recurrence),
+        TodoAction::SetCategory { id, category } => assign_category(todos, &id, category),
+        TodoAction::SetBlockedReason { id, reason } => set_blocked_reason(todos, &id, &reason),
+        TodoAction::Snooze { id } => snooze_todo(todos, &id, ONE_DAY_MS),
+        TodoAction::Due {
+            id,
+            action,
+            now,
+            utc_offset_ms,
+        } => apply_due_action(todos, &id, &action, now, utc_offset_ms),
+        TodoAction::ToggleReview { id } => toggle_require_review(todos, &id),
+        TodoAction::ConfirmReview { id, now, utc_offset_ms } => {
+            spawn_recurrences(todos, &confirm_review(todos, &id, now), now, utc_offset_ms)
+        }
+        TodoAction::AddSubtask { id, title } => try_add_subtask(todos, &id, &title)?,
+        TodoAction::ToggleSubtask {
+            id,
+            subtask_id,
+            now,
+            utc_offset_ms,
+        } => {
+            let toggled = toggle_subtask(todos, &id, &subtask_id);
+            let completed = complete_if_subtasks_done(&toggled, &id, now);
+            spawn_recurrences(todos, &completed, now, utc_offset_ms)
+        }
+        TodoAction::DeleteSubtask { id, subtask_id } => delete_subtask(todos, &id, &subtask_id),
+        TodoAction::CompleteVisible { ids, now, utc_offset_ms } => {
+            spawn_recurrences(todos, &toggle_visible(todos, &ids, true, now), now, utc_offset_ms)
+        }
+        TodoAction::DeleteMany { ids } => delete_many(todos, &ids),
+        TodoAction::ClearTags { ids } => clear_tags_many(todos, &ids),
+        TodoAction::RolloverOverdue { now, utc_offset_ms } => rollover_overdue(todos, now, utc_offset_ms),
+        TodoAction::Dedupe => dedupe_todos(todos),
+        TodoAction::ClearCompleted => clear_completed(todos),
+        TodoAction::ClearAll => Vec::new(),
+        TodoAction::Replace { todos } => todos,
+        TodoAction::Batch(actions) => {
+            return actions
+                .into_iter()
+                .try_fold(todos.to_vec(), |todos, action| reduce(&todos, action));
+        }
+    })
+}
+
+type TodoChange = (Vec<Todo>, Vec<Todo>);
+
+type CommandOutcome = std::rc::Rc<RefCell<Option<Result<TodoChange, String>>>>;
+
+struct TodoCommand {
+    action: TodoAction,
+    now: f64,
+    outcome: CommandOutcome,
+}
+
+impl Apply for Vec<Todo> {
+    type Action = TodoCommand;
+
+    fn apply(&self, command: TodoCommand) -> Option<Self> {
+        let (result, snapshot) = with_snapshot(self, |todos| reduce(todos, command.action));
+        let result = result.map(|new_todos| stamp_updated(self, &new_todos, command.now));
+        let applied = result.as_ref().ok().filter(|new_todos| *new_todos != self).cloned();
+        *command.outcome.borrow_mut() = Some(result.map(|new_todos| (snapshot, new_todos)));
+        applied
+    }
+}
+
+fn apply_action(todos_handle: &TodosHandle, action: TodoAction) -> Result<Option<TodoChange>, String> {
+    let outcome = CommandOutcome::default();
+    todos_handle.dispatch(HistoryAction::Apply(TodoCommand {
+        action,
+        now: js_sys::Date::now(),
+        outcome: outcome.clone(),
+    }));
+    let applied = outcome.take().transpose()?;
+    Ok(applied.filter(|(old_todos, new_todos)| old_todos != new_todos))
+}
+
+fn try_dispatch(
+    todos_handle: &TodosHandle,
+    action: TodoAction,
+    error_handle: &UseStateHandle<Option<String>>,
+) -> Result<(), String> {
+    if let Some((old_todos, new_todos)) = apply_action(todos_handle, action)? {
+        persist_todos(&old_todos, &new_todos, error_handle);
+    }
+    Ok(())
+}
+
+fn dispatch(todos_handle: &TodosHandle, action: TodoAction, error_handle: &UseStateHandle<Option<String>>) {
+    if let Err(e) = try_dispatch(todos_handle, action, error_handle) {
+        error_handle.set(Some(e));
+    }
+}
+
+fn update_todos_destructive(
+    todos_handle: &TodosHandle,
+    action: TodoAction,
+    message: &'static str,
+    snapshot_handle: &UseStateHandle<Option<(&'static str, Vec<Todo>)>>,
+    error_handle: &UseStateHandle<Option<String>>,
+) {
+    match apply_action(todos_handle, action) {
+        Ok(Some((snapshot, new_todos))) => {
+            persist_todos(&snapshot, &new_todos, error_handle);
+            snapshot_handle.set(Some((message, snapshot)));
+        }
+        Ok(None) => {}
+        Err(e) => error_handle.set(Some(e)),
+    }
+}
+
+fn clear_snapshot(snapshot_handle: &UseStateHandle<Option<(&'static str, Vec<Todo>)>>) {
+    snapshot_handle.set(None);
+}
+
+fn clear_input(input: &HtmlInputElement) {
+    input.set_value("");
+}
+
+fn delete_todo(todos: &[Todo], id: &str) -> Vec<Todo> {
+    todos.iter().filter(|todo| todo.id != id).cloned().collect()
+}
+
+fn move_selection_to_edge(todos: &[Todo], selected: &HashSet<String>, to_top: bool) -> Vec<Todo> {
+    let ordered = sort_by_order(todos);
+    let arrange = |pinned: bool| {
+        let (moved, rest): (Vec<Todo>, Vec<Todo>) = ordered
+            .iter()
+            .filter(|todo| todo.pinned == pinned)
+            .cloned()
+            .partition(|todo| selected.contains(&todo.id));
+        if to_top {
+            moved.into_iter().chain(rest)
+        } else {
+            rest.into_iter().chain(moved)
+        }
+    };
+    let (mut pinned, mut unpinned) = (arrange(true), arrange(false));
+    renumber(
+        ordered
+            .iter()
+            .filter_map(|todo| if todo.pinned { pinned.next() } else { unpinned.next() })
+            .collect(),
+    )
+}
+
+fn advance_clear_all(confirming: bool, todos: &[Todo]) -> (bool, Option<Vec<Todo>>) {
+    if todos.is_empty() {
+        (false, None)
+    } else if confirming {
+        (false, Some(Vec::new()))
+    } else {
+        (true, None)
+    }
+}
+
+fn needs_confirm(completed_count: usize) -> bool {
+    completed_count > CONFIRM_THRESHOLD
+}
+
+fn clear_completed(todos: &[Todo]) -> Vec<Todo> {
+    todos
+        .iter()
+        .filter(|todo| !todo.completed || todo.archived)
+        .cloned()
+        .collect()
+}
+
+fn advance_clear_completed(confirming: bool, todos: &[Todo]) -> (bool, Option<Vec<Todo>>) {
+    let completed_count = todos
+        .iter()
+        .filter(|todo| todo.completed && !todo.archived)
+        .count();
+    if completed_count == 0 {
+        (false, None)
+    } else if confirming || !needs_confirm(completed_count) {
+        (false, Some(clear_completed(todos)))
+    } else {
+        (true, None)
+    }
+}
+
+fn delete_many(todos: &[Todo], ids: &HashSet<String>) -> Vec<Todo> {
+    todos.iter().filter(|todo| !ids.contains(&todo.id)).cloned().collect()
+}
+
+fn clear_tags_many(todos: &[Todo], ids: &HashSet<String>) -> Vec<Todo> {
+    todos
+        .iter()
+        .map(|todo| {
+            if ids.contains(&todo.id) {
+                Todo {
+                    tags: Vec::new(),
+                    ..todo.clone()
+                }
+            } else {
+                todo.clone()
+            }
+        })
+        .collect()
+}
+
+fn toggle_selected(selected: &HashSet<String>, id: &str) -> HashSet<String> {
+    let mut new_selected = selected.clone();
+    if !new_selected.remove(id) {
+        new_selected.insert(id.to_string());
+    }
+    new_selected
+}
+
+fn select_all_state(selected: &HashSet<String>, visible_ids: &HashSet<String>) -> Option<bool> {
+    let selected_count = visible_ids.iter().filter(|id| selected.contains(*id)).count();
+    if selected_count == 0 {
+        Some(false)
+    } else if selected_count == visible_ids.len() {
+        Some(true)
+    } else {
+        None
+    }
+}
+
+fn set_all_selected(selected: &HashSet<String>, visible_ids: &HashSet<String>, select: bool) -> HashSet<String> {
+    if select {
+        selected.union(visible_ids).cloned().collect()
+    } else {
+        selected.difference(visible_ids).cloned().collect()
+    }
+}
+
+fn can_complete(todo: &Todo) -> bool {
+    !todo.require_review || todo.review_confirmed
+}
+
+fn toggle_expanded(expanded: &HashSet<String>, id: &str) -> HashSet<String> {
+    let mut new_expanded = expanded.clone();
+    if !new_expanded.remove(id) {
+        new_expanded.insert(id.to_string());
+    }
+    new_expanded
+}
+
+fn toggle_todo(todos: &[Todo], id: &str, now: f64) -> Vec<Todo> {
+    todos
+        .iter()
+        .map(|todo| {
+            if todo.id != id || (!todo.completed && !can_complete(todo)) {
+                todo.clone()
+            } else if todo.completed {
+                Todo {
+                    completed: false,
+                    completed_at: None,
+                    review_confirmed: false,
+                    ..todo.clone()
+                }
+            } else {
+                Todo {
+                    completed: true,
+                    completed_at: Some(now),
+                    ..todo.clone()
+                }
+            }
+        })
+        .collect()
+}
+
+fn toggle_visible(todos: &[Todo], visible_ids: &HashSet<String>, completed: bool, now: f64) -> Vec<Todo> {
+    todos
+        .iter()
+        .map(|todo| {
+            if !visible_ids.contains(&todo.id)
+                || todo.completed == completed
+                || (completed && !can_complete(todo))
+            {
+                todo.clone()
+            } else if completed {
+                Todo {
+                    completed: true,
+                    completed_at: Some(now),
+                    ..todo.clone()
+                }
+            } else {
+                Todo {
+                    completed: false,
+                    completed_at: None,
+                    review_confirmed: false,
+                    ..todo.clone()
+                }
+            }
+        })
+        .collect()
+}
+
+fn confirm_review(todos: &[Todo], id: &str, now: f64) -> Vec<Todo> {
+    let confirmed: Vec<Todo> = todos
+        .iter()
+        .map(|todo| {
+            if todo.id == id && !todo.completed {
+                Todo {
+                    review_confirmed: true,
+                    ..todo.clone()
+                }
+            } else {
+                todo.clone()
+            }
+        })
+        .collect();
+    toggle_todo(&confirmed, id, now)
+}
+
+fn toggle_require_review(todos: &[Todo], id: &str) -> Vec<Todo> {
+    todos
+        .iter()
+        .map(|todo| {
+            if todo.id == id {
+                Todo {
+                    require_review: !todo.require_review,
+                    review_confirmed: false,
+                    ..todo.clone()
+                }
+            } else {
+                todo.clone()
+            }
+        })
+        .collect()
+}
+
+fn update_subtasks(
+    todos: &[Todo],
+    todo_id: &str,
+    mutate: impl Fn(&[Subtask]) -> Vec<Subtask>,
+) -> Vec<Todo> {
+    todos
+        .iter()
+        .map(|todo| {
+            if todo.id == todo_id {
+                Todo {
+                    subtasks: mutate(&todo.subtasks),
+                    ..todo.clone()
+                }
+            } else {
+                todo.clone()
+            }
+        })
+        .collect()
+}
+
+fn add_subtask(todos: &[Todo], todo_id: &str, title: &str) -> Vec<Todo> {
+    update_subtasks(todos, todo_id, |subtasks| {
+        let mut new_subtasks = subtasks.to_vec();
+        new_subtasks.push(Subtask {
+            id: Uuid::new_v4().to_string(),
+            title: title.trim().to_string(),
+            done: false,
+        });
+        new_subtasks
+    })
+}
+
+fn try_add_subtask(todos: &[Todo], todo_id: &str, title: &str) -> Result<Vec<Todo>, String> {
+    if title.trim().is_empty() {
+        return Err("Subtask can't be empty".to_string());
+    }
+    if !is_valid_title(title) {
+        return Err(format!("Subtask must be at most {} characters", MAX_TITLE_LEN));
+    }
+    Ok(add_subtask(todos, todo_id, title))
+}
+
+fn toggle_subtask(todos: &[Todo], todo_id: &str, subtask_id: &str) -> Vec<Todo> {
+    update_subtasks(todos, todo_id, |subtasks| {
+        subtasks
+            .iter()
+            .map(|subtask| {
+                if subtask.id == subtask_id {
+                    Subtask {
+                        done: !subtask.done,
+                        ..subtask.clone()
+                    }
+                } else {
+                    subtask.clone()
+                }
+            })
+            .collect()
+    })
+}
+
+fn delete_subtask(todos: &[Todo], todo_id: &str, subtask_id: &str) -> Vec<Todo> {
+    update_subtasks(todos, todo_id, |subtasks| {
+        subtasks
+            .iter()
+            .filter(|subtask| subtask.id != subtask_id)
+            .cloned()
+            .collect()
+    })
+}
+
+fn subtask_progress(todo: &Todo) -> Option<(usize, usize)> {
+    if todo.subtasks.is_empty() {
+        return None;
+    }
+    let done = todo.subtasks.iter().filter(|subtask| subtask.done).count();
+    Some((done, todo.subtasks.len()))
+}
+
+fn complete_if_subtasks_done(todos: &[Todo], todo_id: &str, now: f64) -> Vec<Todo> {
+    let all_done = todos.iter().any(|todo| {
+        todo.id == todo_id
+            && !todo.completed
+            && !todo.subtasks.is_empty()
+            && todo.subtasks.iter().all(|subtask| subtask.done)
+    });
+    if all_done {
+        toggle_todo(todos, todo_id, now)
+    } else {
+        todos.to_vec()
+    }
+}
+
+fn push_title_history(history: &[(f64, String)], now: f64, old_title: &str) -> Vec<(f64, String)> {
+    let skip = (history.len() + 1).saturating_sub(MAX_TITLE_HISTORY);
+    let mut new_history: Vec<(f64, String)> = history.iter().skip(skip).cloned().collect();
+    new_history.push((now, old_title.to_string()));
+    new_history
+}
+
+fn update_todo_title(todos: &[Todo], id: &str, title: &str, now: f64) -> Vec<Todo> {
+    let title = title.trim();
+    if !is_valid_title(title) {
+        return todos.to_vec();
+    }
+    todos
+        .iter()
+        .map(|todo| {
+            if todo.id == id && todo.title != title {
+                Todo {
+                    title: title.to_string(),
+                    title_history: push_title_history(&todo.title_history, now, &todo.title),
+                    ..todo.clone()
+                }
+            } else {
+                todo.clone()
+            }
+        })
+        .collect()
+}
+
+fn dedupe_todos(todos: &[Todo]) -> Vec<Todo> {
+    let mut kept: Vec<Todo> = Vec::with_capacity(todos.len());
+    for todo in todos {
+        if todo.archived {
+            kept.push(todo.clone());
+            continue;
+        }
+        let key = todo.title.trim().to_lowercase();
+        match kept
+            .iter_mut()
+            .find(|existing| !existing.archived && existing.title.trim().to_lowercase() == key)
+        {
+            Some(existing) if todo.completed && !existing.completed && can_complete(existing) => {
+                existing.completed = true;
+                existing.completed_at = todo.completed_at;
+            }
+            Some(_) => {}
+            None => kept.push(todo.clone()),
+        }
+    }
+    kept
+}
+
+fn duplicate_todo(todos: &[Todo], id: &str, now: f64) -> Vec<Todo> {
+    let mut new_todos = sort_by_order(todos);
+    let Some(index) = new_todos.iter().position(|todo| todo.id == id) else {
+        return todos.to_vec();
+    };
+    let original = new_todos[index].clone();
+    new_todos.insert(
+        index + 1,
+        Todo {
+            id: Uuid::new_v4().to_string(),
+            title: format!("{} (copy)", original.title),
+            completed: false,
+            completed_at: None,
+            review_confirmed: false,
+            title_history: Vec::new(),
+            created_at: Some(now),
+            updated_at: None,
+            ..original.clone()
+        },
+    );
+    renumber(new_todos)
+}
+
+fn swap_with_visible_neighbour(todos: &[Todo], id: &str, visible: &[String], up: bool) -> Vec<Todo> {
+    let mut new_todos = sort_by_order(todos);
+    let neighbour = visible
+        .iter()
+        .position(|visible_id| visible_id == id)
+        .and_then(|index| if up { index.checked_sub(1) } else { Some(index + 1) })
+        .and_then(|index| visible.get(index));
+    let position = |id: &str| new_todos.iter().position(|todo| todo.id == id);
+    if let Some((from, to)) = neighbour
+        .filter(|neighbour| validate_reorder(todos, id, neighbour))
+        .and_then(|neighbour| position(id).zip(position(neighbour)))
+    {
+        new_todos.swap(from, to);
+    }
+    renumber(new_todos)
+}
+
+fn move_todo_up(todos: &[Todo], id: &str, visible: &[String]) -> Vec<Todo> {
+    swap_with_visible_neighbour(todos, id, visible, true)
+}
+
+fn move_todo_down(todos: &[Todo], id: &str, visible: &[String]) -> Vec<Todo> {
+    swap_with_visible_neighbour(todos, id, visible, false)
+}
+
+fn toggle_pin(todos: &[Todo], id: &str) -> Vec<Todo> {
+    todos
+        .iter()
+        .map(|todo| {
+            if todo.id == id {
+                Todo {
+                    pinned: !todo.pinned,
+                    ..todo.clone()
+                }
+            } else {
+                todo.clone()
+            }
+        })
+        .collect()
+}
+
+fn apply_pins(todos: &[Todo]) -> Vec<Todo> {
+    let (pinned, unpinned): (Vec<Todo>, Vec<Todo>) =
+        todos.iter().cloned().partition(|todo| todo.pinned);
+    pinned.into_iter().chain(unpinned).collect()
+}
+
+fn toggle_star(todos: &[Todo], id: &str) -> Vec<Todo> {
+    todos
+        .iter()
+        .map(|todo| {
+            if todo.id == id {
+                Todo {
+                    starred: !todo.starred,
+                    ..todo.clone()
+                }
+            } else {
+                todo.clone()
+            }
+        })
+        .collect()
+}
+
+fn set_priority(todos: &[Todo], id: &str, priority: Priority) -> Vec<Todo> {
+    todos
+        .iter()
+        .map(|todo| {
+            if todo.id == id {
+                Todo {
+                    priority,
+                    ..todo.clone()
+                }
+            } else {
+                todo.clone()
+            }
+        })
+        .collect()
+}
+
+fn effective_priority(todo: &Todo, now: f64) -> Priority {
+    let escalated = match todo.due_at {
+        Some(due_at) if due_at - now <= ESCALATE_TO_HIGH_MS => Priority::High,
+        Some(due_at) if due_at - now <= ESCALATE_TO_MEDIUM_MS => Priority::Medium,
+        _ => Priority::Low,
+    };
+    todo.priority.max(escalated)
+}
+
+fn sort_todos(todos: &[Todo], mode: SortMode, now: f64) -> Vec<Todo> {
+    let mut sorted = todos.to_vec();
+    match mode {
+        SortMode::Manual => {}
+        SortMode::Priority => {
+            sorted.sort_by_key(|todo| std::cmp::Reverse(effective_priority(todo, now)))
+        }
+        SortMode::Starred => sorted.sort_by_key(|todo| !todo.starred),
+        SortMode::Due => sorted.sort_by(|a, b| match (a.due_at, b.due_at) {
+            (Some(a), Some(b)) => a.total_cmp(&b),
+            (Some(_), None) => std::cmp::Ordering::Less,
+            (None, Some(_)) => std::cmp::Ordering::Greater,
+            (None, None) => std::cmp::Ordering::Equal,
+        }),
+        SortMode::Alphabetical => sorted = sort_alphabetical(&sorted),
+    }
+    sorted
+}
+
+fn fuzzy_match(title: &str, query: &str) -> Option<i64> {
+    let query: Vec<char> = query
+        .to_lowercase()
+        .chars()
+        .filter(|c| !c.is_whitespace())
+        .collect();
+    if query.is_empty() {
+        return Some(0);
+    }
+    let title: Vec<char> = title.to_lowercase().chars().collect();
+    let mut score = 0;
+    let mut matched = 0;
+    let mut previous: Option<usize> = None;
+    for (index, c) in title.iter().enumerate() {
+        if matched == query.len() {
+            break;
+        }
+        if *c != query[matched] {
+            continue;
+        }
+        score += 1;
+        if index == 0 || !title[index - 1].is_alphanumeric() {
+            score += 3;
+        }
+        match previous {
+            Some(prev) if prev + 1 == index => score += 5,
+            Some(prev) => score -= (index - prev - 1) as i64,
+            None => score -= index as i64,
+        }
+        previous = Some(index);
+        matched += 1;
+    }
+    (matched == query.len()).then_some(score)
+}
+
+fn fuzzy_filter(todos: &[Todo], query: &str) -> Vec<Todo> {
+    if query.trim().is_empty() {
+        return todos.to_vec();
+    }
+    let mut scored: Vec<(i64, Todo)> = todos
+        .iter()
+        .filter_map(|todo| fuzzy_match(&todo.title, query).map(|score| (score, todo.clone())))
+        .collect();
+    scored.sort_by_key(|(score, _)| std::cmp::Reverse(*score));
+    scored.into_iter().map(|(_, todo)| todo).collect()
+}
+
+fn sort_alphabetical(todos: &[Todo]) -> Vec<Todo> {
+    let mut sorted = todos.to_vec();
+    sorted.sort_by_cached_key(|todo| todo.title.to_lowercase());
+    sorted
+}
+
+fn partition_completed(todos: &[Todo]) -> Vec<Todo> {
+    let (active, completed): (Vec<Todo>, Vec<Todo>) =
+        todos.iter().cloned().partition(|todo| !todo.completed);
+    active.into_iter().chain(completed).collect()
+}
+
+fn initial_sort_mode(mode: SortMode, apply_on_load: bool) -> SortMode {
+    if apply_on_load {
+        mode
+    } else {
+        SortMode::Manual
+    }
+}
+
+fn should_animate(enabled: bool, reduced_motion: bool) -> bool {
+    enabled && !reduced_motion
+}
+
+fn flip_deltas(before: &HashMap<String, f64>, after: &HashMap<String, f64>) -> Vec<(String, f64)> {
+    let mut deltas: Vec<(String, f64)> = after
+        .iter()
+        .filter_map(|(id, top)| {
+            let delta = before.get(id)? - top;
+            (delta != 0.0).then(|| (id.clone(), delta))
+        })
+        .collect();
+    deltas.sort_by(|a, b| a.0.cmp(&b.0));
+    deltas
+}
+
+fn prefers_reduced_motion() -> bool {
+    web_sys::window()
+        .and_then(|window| window.match_media("(prefers-reduced-motion: reduce)").ok().flatten())
+        .is_some_and(|query| query.matches())
+}
+
+fn row_elements() -> Vec<(String, web_sys::HtmlElement)> {
+    let Some(rows) = web_sys::window()
+        .and_then(|window| window.document())
+        .and_then(|document| document.query_selector_all("li[data-id]").ok())
+    else {
+        return Vec::new();
+    };
+    (0..rows.length())
+        .filter_map(|index| rows.get(index)?.dyn_into::<web_sys::HtmlElement>().ok())
+        .filter_map(|row| Some((row.get_attribute("data-id")?, row)))
+        .collect()
+}
+
+fn row_positions() -> HashMap<String, f64> {
+    row_elements()
+        .into_iter()
+        .map(|(id, row)| (id, row.get_bounding_client_rect().top()))
+        .collect()
+}
+
+fn capture_row_positions(flip_before: &RefCell<Option<HashMap<String, f64>>>) {
+    if should_animate(ANIMATE_REORDER, prefers_reduced_motion()) {
+        *flip_before.borrow_mut() = Some(row_positions());
+    }
+}
+
+fn play_flip(before: &HashMap<String, f64>) {
+    let rows: HashMap<String, web_sys::HtmlElement> = row_elements().into_iter().collect();
+    for (id, delta) in flip_deltas(before, &row_positions()) {
+        let Some(row) = rows.get(&id) else {
+            continue;
+        };
+        let style = row.style();
+        let _ = style.set_property("transition", "none");
+        let _ = style.set_property("transform", &format!("translateY({}px)", delta));
+        let _ = row.offset_height();
+        let _ = style.set_property("transition", &format!("transform {}ms ease", REORDER_ANIMATION_MS));
+        let _ = style.set_property("transform", "");
+    }
+}
+
+fn reorder(todos: &[Todo], from: usize, to: usize) -> Vec<Todo> {
+    let mut new_todos = sort_by_order(todos);
+    if from != to
+        && from < todos.len()
+        && to < todos.len()
+        && new_todos[from].pinned == new_todos[to].pinned
+    {
+        let todo = new_todos.remove(from);
+        new_todos.insert(to, todo);
+    }
+    renumber(new_todos)
+}
+
+struct RowLayout<'a> {
+    due_grouped: bool,
+    grouped: bool,
+    expanded_group: Option<&'a str>,
+}
+
+fn rendered_row_ids(
+    visible: &[Todo],
+    page: &[Todo],
+    layout: RowLayout,
+    now: f64,
+    utc_offset_ms: f64,
+) -> Vec<String> {
+    if layout.due_grouped {
+        group_by_due(visible, now, utc_offset_ms)
+            .sections()
+            .into_iter()
+            .flat_map(|(_, group)| group.iter().map(|todo| todo.id.clone()))
+            .collect()
+    } else if layout.grouped {
+        group_by_tag(visible)
+            .into_iter()
+            .filter(|(key, _)| layout.expanded_group == Some(key.as_str()))
+            .flat_map(|(_, group)| group.into_iter().map(|todo| todo.id))
+            .collect()
+    } else {
+        page.iter().map(|todo| todo.id.clone()).collect()
+    }
+}
+
+fn filter_todos(todos: &[Todo], filter: Filter) -> Vec<Todo> {
+    todos.iter().filter(|todo| filter.matches(todo)).cloned().collect()
+}
+
+fn group_by_tag(todos: &[Todo]) -> Vec<(String, Vec<Todo>)> {
+    let mut groups: BTreeMap<String, Vec<Todo>> = BTreeMap::new();
+    let mut untagged = Vec::new();
+    for todo in todos {
+        if todo.tags.is_empty() {
+            untagged.push(todo.clone());
+        }
+        for tag in &todo.tags {
+            groups.entry(format!("#{}", tag)).or_default().push(todo.clone());
+        }
+    }
+    let mut groups: Vec<(String, Vec<Todo>)> = groups.into_iter().collect();
+    if !untagged.is_empty() {
+        groups.push(("Untagged".to_string(), untagged));
+    }
+    groups
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+struct DueGroups {
+    overdue: Vec<Todo>,
+    today: Vec<Todo>,
+    upcoming: Vec<Todo>,
+    no_date: Vec<Todo>,
+}
+
+impl DueGroups {
+    fn sections(&self) -> [(&'static str, &[Todo]); 4] {
+        [
+            ("Overdue", &self.overdue),
+            ("Today", &self.today),
+            ("Upcoming", &self.upcoming),
+            ("No date", &self.no_date),
+        ]
+    }
+}
+
+fn is_overdue(todo: &Todo, now: f64, utc_offset_ms: f64) -> bool {
+    let start_of_today = today_ms(now, utc_offset_ms);
+    !todo.completed && todo.due_at.is_some_and(|due_at| due_at < start_of_today)
+}
+
+fn group_by_due(todos: &[Todo], now: f64, utc_offset_ms: f64) -> DueGroups {
+    let end_of_today = tomorrow_ms(now, utc_offset_ms);
+    let mut groups = DueGroups::default();
+    for todo in todos {
+        let bucket = match todo.due_at {
+            None => &mut groups.no_date,
+            Some(_) if is_overdue(todo, now, utc_offset_ms) => &mut groups.overdue,
+            Some(due_at) if due_at < end_of_today => &mut groups.today,
+            Some(_) => &mut groups.upcoming,
+        };
+        bucket.push(todo.clone());
+    }
+    groups
+}
+
+fn toggle_accordion(current: Option<&str>, key: &str) -> Option<String> {
+    if current == Some(key) {
+        None
+    } else {
+        Some(key.to_string())
+    }
+}
+
+fn move_focus(current: Option<usize>, delta: isize, len: usize) -> Option<usize> {
+    if len == 0 {
+        return None;
+    }
+    let last = len - 1;
+    Some(match current {
+        None if delta < 0 => last,
+        None => 0,
+        Some(index) => index.min(last).saturating_add_signed(delta).min(last),
+    })
+}
+
+fn page_count(len: usize, size: usize) -> usize {
+    len.div_ceil(size.max(1)).max(1)
+}
+
+fn paginate(todos: &[Todo], page: usize, size: usize) -> &[Todo] {
+    let size = size.max(1);
+    let page = page.min(page_count(todos.len(), size) - 1);
+    let start = (page * size).min(todos.len());
+    let end = (start + size).min(todos.len());
+    &todos[start..end]
+}
+
+fn filter_counts(todos: &[Todo]) -> (usize, usize, usize) {
+    todos
+        .iter()
+        .filter(|todo| !todo.archived)
+        .fold((0, 0, 0), |(all, active, completed), todo| {
+            if todo.completed {
+                (all + 1, active, completed + 1)
+            } else {
+                (all + 1, active + 1, completed)
+            }
+        })
+}
+
+fn filter_count(filter: Filter, (all, active, completed): (usize, usize, usize)) -> usize {
+    match filter {
+        Filter::All => all,
+        Filter::Active => active,
+        Filter::Completed => completed,
+    }
+}
+
+fn is_filter_selectable(filter: Filter, counts: (usize, usize, usize)) -> bool {
+    filter == Filter::All || filter_count(filter, counts) > 0
+}
+
+fn count_active(todos: &[Todo]) -> usize {
+    todos
+        .iter()
+        .filter(|todo| !todo.completed && !todo.archived)
+        .count()
+}
+
+fn format_doc_title(active: usize) -> String {
+    if active == 0 {
+        APP_TITLE.to_string()
+    } else {
+        format!("{} ({} active)", APP_TITLE, active)
+    }
+}
+
+fn review_queue(todos: &[Todo]) -> Vec<String> {
+    let mut active: Vec<&Todo> = todos
+        .iter()
+        .filter(|todo| !todo.completed && !todo.archived)
+        .collect();
+    active.sort_by(|a, b| match (a.created_at, b.created_at) {
+        (Some(a), Some(b)) => a.total_cmp(&b),
+        (None, Some(_)) => std::cmp::Ordering::Less,
+        (Some(_), None) => std::cmp::Ordering::Greater,
+        (None, None) => std::cmp::Ordering::Equal,
+    });
+    active.into_iter().map(|todo| todo.id.clone()).collect()
+}
+
+fn active_points(todos: &[Todo]) -> u32 {
+    todos
+        .iter()
+        .filter(|todo| !todo.completed && !todo.archived)
+        .filter_map(|todo| todo.points)
+        .fold(0u32, u32::saturating_add)
+}
+
+fn over_capacity(todos: &[Todo], capacity: u32) -> bool {
+    active_points(todos) > capacity
+}
+
+fn parse_points(value: &str) -> Option<u32> {
+    value.trim().parse().ok()
+}
+
+fn set_points(todos: &[Todo], id: &str, points: Option<u32>) -> Vec<Todo> {
+    todos
+        .iter()
+        .map(|todo| {
+            if todo.id == id {
+                Todo {
+                    points,
+                    ..todo.clone()
+                }
+            } else {
+                todo.clone()
+            }
+        })
+        .collect()
+}
+
+fn set_estimate_minutes(todos: &[Todo], id: &str, estimate_minutes: Option<u32>) -> Vec<Todo> {
+    todos
+        .iter()
+        .map(|todo| {
+            if todo.id == id {
+                Todo {
+                    estimate_minutes,
+                    ..todo.clone()
+                }
+            } else {
+                todo.clone()
+            }
+        })
+        .collect()
+}
+
+fn set_actual_minutes(todos: &[Todo], id: &str, actual_minutes: Option<u32>) -> Vec<Todo> {
+    todos
+        .iter()
+        .map(|todo| {
+            if todo.id == id {
+                Todo {
+                    actual_minutes,
+                    ..todo.clone()
+                }
+            } else {
+                todo.clone()
+            }
+        })
+        .collect()
+}
+
+fn sum_estimates(todos: &[Todo]) -> (u32, u32) {
+    todos
+        .iter()
+        .filter(|todo| !todo.completed && !todo.archived)
+        .fold((0, 0), |(estimate, actual), todo| {
+            (
+                estimate.saturating_add(todo.estimate_minutes.unwrap_or(0)),
+                actual.saturating_add(todo.actual_minutes.unwrap_or(0)),
+            )
+        })
+}
+
+fn completions_per_day(todos: &[Todo], now: f64) -> f64 {
+    completed_within(todos, THROUGHPUT_WINDOW_DAYS * ONE_DAY_MS, now).len() as f64
+        / THROUGHPUT_WINDOW_DAYS
+}
+
+fn completions_by_day(todos: &[Todo], now: f64, utc_offset_ms: f64) -> [usize; 7] {
+    let today = day_number(now, utc_offset_ms);
+    let mut counts = [0; 7];
+    for completed_at in todos
+        .iter()
+        .filter(|todo| todo.completed)
+        .filter_map(|todo| todo.completed_at)
+    {
+        let days_ago = today - day_number(completed_at, utc_offset_ms);
+        if (0..7).contains(&days_ago) {
+            counts[days_ago as usize] += 1;
+        }
+    }
+    counts
+}
+
+fn render_completion_chart(counts: &[usize; 7], now: f64, utc_offset_ms: f64) -> Html {
+    let max = counts.iter().copied().max().unwrap_or(0).max(1);
+    html! {
+        <div class="flex items-end gap-1 h-16 mb-2" aria-label="Completed per day, last 7 days">
+            { for (0..7).rev().map(|days_ago| {
+                let count = counts[days_ago];
+                let day = now - days_ago as f64 * ONE_DAY_MS;
+                html! {
+                    <div class="flex flex-col items-center flex-1 h-full justify-end" title={format!("{} completed", count)}>
+                        <div class="w-full bg-green-400 rounded-t" style={format!("height: {}%", count * 100 / max)} />
+                        <span class="text-xs text-gray-500">{ weekday_label(day, utc_offset_ms) }</span>
+                    </div>
+                }
+            })}
+        </div>
+    }
+}
+
+fn projected_completion(index: usize, per_day: f64, now: f64) -> Option<f64> {
+    (per_day > 0.0).then(|| now + (index + 1) as f64 / per_day * ONE_DAY_MS)
+}
+
+fn weekday_label(ms: f64, utc_offset_ms: f64) -> &'static str {
+    WEEKDAY_LABELS[(day_number(ms, utc_offset_ms) + 4).rem_euclid(7) as usize]
+}
+
+fn set_blocked_reason(todos: &[Todo], id: &str, reason: &str) -> Vec<Todo> {
+    let reason = reason.trim();
+    todos
+        .iter()
+        .map(|todo| {
+            if todo.id == id {
+                Todo {
+                    blocked_reason: (!reason.is_empty()).then(|| reason.to_string()),
+                    ..todo.clone()
+                }
+            } else {
+                todo.clone()
+            }
+        })
+        .collect()
+}
+
+fn contexts(todos: &[Todo]) -> Vec<String> {
+    let mut contexts: Vec<String> = todos.iter().filter_map(|todo| todo.context.clone()).collect();
+    contexts.sort();
+    contexts.dedup();
+    contexts
+}
+
+fn filter_by_context(todos: &[Todo], context: &str) -> Vec<Todo> {
+    todos
+        .iter()
+        .filter(|todo| todo.context.as_deref() == Some(context))
+        .cloned()
+        .collect()
+}
+
+fn filter_blocked(todos: &[Todo]) -> Vec<Todo> {
+    todos
+        .iter()
+        .filter(|todo| todo.blocked_reason.as_deref().is_some_and(|reason| !reason.trim().is_empty()))
+        .cloned()
+        .collect()
+}
+
+fn completed_within(todos: &[Todo], window_ms: f64, now: f64) -> Vec<Todo> {
+    todos
+        .iter()
+        .filter(|todo| {
+            todo.completed
+                && todo
+                    .completed_at
+                    .is_some_and(|completed_at| now - completed_at <= window_ms)
+        })
+        .cloned()
+        .collect()
+}
+
+fn completed_today(todos: &[Todo], now: f64, utc_offset_ms: f64) -> Vec<Todo> {
+    let midnight = today_ms(now, utc_offset_ms);
+    todos
+        .iter()
+        .filter(|todo| {
+            todo.completed && todo.completed_at.is_some_and(|completed_at| completed_at >= midnight)
+        })
+        .cloned()
+        .collect()
+}
+
+fn validate_reorder(todos: &[Todo], from_id: &str, to_id: &str) -> bool {
+    let find = |id: &str| todos.iter().find(|todo| todo.id == id);
+    match (find(from_id), find(to_id)) {
+        (Some(from), Some(to)) => from.pinned == to.pinned,
+        _ => false,
+    }
+}
+
+fn set_archived(todos: &[Todo], id: &str, archived: bool) -> Vec<Todo> {
+    todos
+        .iter()
+        .map(|todo| {
+            if todo.id == id {
+                Todo {
+                    archived,
+                    ..todo.clone()
+                }
+            } else {
+                todo.clone()
+            }
+        })
+        .collect()
+}
+
+fn archive_todo(todos: &[Todo], id: &str) -> Vec<Todo> {
+    set_archived(todos, id, true)
+}
+
+fn restore_todo(todos: &[Todo], id: &str) -> Vec<Todo> {
+    set_archived(todos, id, false)
+}
+
+fn snooze_todo(todos: &[Todo], id: &str, by_ms: f64) -> Vec<Todo> {
+    todos
+        .iter()
+        .map(|todo| match todo.due_at {
+            Some(due_at) if todo.id == id => Todo {
+                due_at: Some(due_at + by_ms),
+                snooze_count: todo.snooze_count + 1,
+                ..todo.clone()
+            },
+            _ => todo.clone(),
+        })
+        .collect()
+}
+
+fn local_utc_offset_ms(now: f64) -> f64 {
+    -js_sys::Date::new(&now.into()).get_timezone_offset() * 60.0 * 1000.0
+}
+
+fn today_ms(now: f64, utc_offset_ms: f64) -> f64 {
+    let local = now + utc_offset_ms;
+    local - local.rem_euclid(ONE_DAY_MS) - utc_offset_ms
+}
+
+fn tomorrow_ms(now: f64, utc_offset_ms: f64) -> f64 {
+    today_ms(now, utc_offset_ms) + ONE_DAY_MS
+}
+
+fn next_week_ms(now: f64, utc_offset_ms: f64) -> f64 {
+    today_ms(now, utc_offset_ms) + 7.0 * ONE_DAY_MS
+}
+
+fn day_number(now: f64, utc_offset_ms: f64) -> i64 {
+    ((now + utc_offset_ms) / ONE_DAY_MS).floor() as i64
+}
+
+fn should_rollover(last_rollover_day: Option<i64>, today: i64) -> bool {
+    last_rollover_day.is_none_or(|day| day < today)
+}
+
+fn rollover_overdue(todos: &[Todo], now: f64, utc_offset_ms: f64) -> Vec<Todo> {
+    let today = today_ms(now, utc_offset_ms);
+    todos
+        .iter()
+        .map(|todo| {
+            if is_overdue(todo, now, utc_offset_ms) {
+                Todo {
+                    due_at: Some(today),
+                    ..todo.clone()
+                }
+            } else {
+                todo.clone()
+            }
+        })
+        .collect()
+}
+
+fn days_from_civil(year: i64, month: i64, day: i64) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let year_of_era = year - era * 400;
+    let month_index = (month + 9) % 12;
+    let day_of_year = (153 * month_index + 2) / 5 + day - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    era * 146_097 + day_of_era - 719_468
+}
+
+fn civil_from_days(days: i64) -> (i64, i64, i64) {
+    let days = days + 719_468;
+    let era = days.div_euclid(146_097);
+    let day_of_era = days - era * 146_097;
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_index = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * month_index + 2) / 5 + 1;
+    let month = if month_index < 10 { month_index + 3 } else { month_index - 9 };
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+    (year, month, day)
+}
+
+fn days_in_month(year: i64, month: i64) -> i64 {
+    let (next_year, next_month) = if month == 12 { (year + 1, 1) } else { (year, month + 1) };
+    days_from_civil(next_year, next_month, 1) - days_from_civil(year, month, 1)
+}
+
+fn add_month(ms: f64, utc_offset_ms: f64) -> f64 {
+    let local = ms + utc_offset_ms;
+    let time_of_day = local.rem_euclid(ONE_DAY_MS);
+    let (year, month, day) = civil_from_days((local / ONE_DAY_MS).floor() as i64);
+    let (year, month) = if month == 12 { (year + 1, 1) } else { (year, month + 1) };
+    let day = day.min(days_in_month(year, month));
+    days_from_civil(year, month, day) as f64 * ONE_DAY_MS + time_of_day - utc_offset_ms
+}
+
+fn next_occurrence(todo: &Todo, now: f64, utc_offset_ms: f64) -> Option<Todo> {
+    let recurrence = todo.recurrence?;
+    let base = todo.due_at.unwrap_or_else(|| today_ms(now, utc_offset_ms));
+    let due_at = match recurrence {
+        Recurrence::Daily => base + ONE_DAY_MS,
+        Recurrence::Weekly => base + 7.0 * ONE_DAY_MS,
+        Recurrence::Monthly => add_month(base, utc_offset_ms),
+    };
+    Some(Todo {
+        id: Uuid::new_v4().to_string(),
+        completed: false,
+        completed_at: None,
+        archived: false,
+        review_confirmed: false,
+        snooze_count: 0,
+        title_history: Vec::new(),
+        actual_minutes: None,
+        subtasks: todo
+            .subtasks
+            .iter()
+            .map(|subtask| Subtask {
+                done: false,
+                ..subtask.clone()
+            })
+            .collect(),
+        due_at: Some(due_at),
+        created_at: Some(now),
+        updated_at: None,
+        ..todo.clone()
+    })
+}
+
+fn spawn_recurrences(old: &[Todo], new: &[Todo], now: f64, utc_offset_ms: f64) -> Vec<Todo> {
+    let mut result = Vec::with_capacity(new.len());
+    for todo in &sort_by_order(new) {
+        result.push(todo.clone());
+        let just_completed = todo.completed
+            && old.iter().any(|previous| previous.id == todo.id && !previous.completed);
+        if just_completed {
+            result.extend(next_occurrence(todo, now, utc_offset_ms));
+        }
+    }
+    if result.len() > new.len() {
+        renumber(result)
+    } else {
+        new.to_vec()
+    }
+}
+
+fn set_recurrence(todos: &[Todo], id: &str, recurrence: Option<Recurrence>) -> Vec<Todo> {
+    todos
+        .iter()
+        .map(|todo| {
+            if todo.id == id {
+                Todo {
+                    recurrence,
+                    ..todo.clone()
+                }
+            } else {
+                todo.clone()
+            }
+        })
+        .collect()
+}
+
+fn assign_category(todos: &[Todo], id: &str, category: Option<Category>) -> Vec<Todo> {
+    todos
+        .iter()
+        .map(|todo| {
+            if todo.id == id {
+                Todo {
+                    category: category.clone(),
+                    ..todo.clone()
+                }
+            } else {
+                todo.clone()
+            }
+        })
+        .collect()
+}
+
+fn is_hex_color(color: &str) -> bool {
+    color
+        .strip_prefix('#')
+        .is_some_and(|hex| matches!(hex.len(), 3 | 6) && hex.chars().all(|c| c.is_ascii_hexdigit()))
+}
+
+fn add_category(categories: &[Category], name: &str, color: &str) -> Vec<Category> {
+    let name = name.trim();
+    let exists = categories
+        .iter()
+        .any(|category| category.name.eq_ignore_ascii_case(name));
+    let mut new_categories = categories.to_vec();
+    if !name.is_empty() && !exists && is_hex_color(color) {
+        new_categories.push(Category {
+            name: name.to_string(),
+            color: color.to_string(),
+        });
+    }
+    new_categories
+}
+
+fn category_border_color(category: Option<&Category>) -> &str {
+    category
+        .map(|category| category.color.as_str())
+        .filter(|color| is_hex_color(color))
+        .unwrap_or(NEUTRAL_BORDER_COLOR)
+}
+
+fn parse_date_input(value: &str, utc_offset_ms: f64) -> Option<f64> {
+    let mut parts = value.split('-').map(|part| part.parse::<i64>().ok());
+    let (year, month, day) = (parts.next()??, parts.next()??, parts.next()??);
+    if parts.next().is_some() || !(1..=12).contains(&month) || !(1..=31).contains(&day) {
+        return None;
+    }
+    Some(days_from_civil(year, month, day) as f64 * ONE_DAY_MS - utc_offset_ms)
+}
+
+fn set_due(todos: &[Todo], id: &str, due_at: Option<f64>) -> Vec<Todo> {
+    todos
+        .iter()
+        .map(|todo| {
+            if todo.id == id {
+                Todo {
+                    due_at,
+                    ..todo.clone()
+                }
+            } else {
+                todo.clone()
+            }
+        })
+        .collect()
+}
+
+fn new_todo_id(old: &[Todo], new: &[Todo]) -> Option<String> {
+    new.iter()
+        .find(|todo| !old.iter().any(|existing| existing.id == todo.id))
+        .map(|todo| todo.id.clone())
+}
+
+fn apply_due_action(
+    todos: &[Todo],
+    id: &str,
+    action: &DueAction,
+    now: f64,
+    utc_offset_ms: f64,
+) -> Vec<Todo> {
+    let is_dated = todos.iter().any(|todo| todo.id == id && todo.due_at.is_some());
+    match action {
+        DueAction::AddDay if is_dated => snooze_todo(todos, id, ONE_DAY_MS),
+        DueAction::AddDay => set_due(todos, id, Some(tomorrow_ms(now, utc_offset_ms))),
+        DueAction::AddWeek if is_dated => snooze_todo(todos, id, 7.0 * ONE_DAY_MS),
+        DueAction::AddWeek => set_due(todos, id, Some(next_week_ms(now, utc_offset_ms))),
+        DueAction::Clear => set_due(todos, id, None),
+        DueAction::Pick(value) => match parse_date_input(value, utc_offset_ms) {
+            Some(due_at) => set_due(todos, id, Some(due_at)),
+            None => todos.to_vec(),
+        },
+    }
+}
+
+fn format_date(ms: f64) -> String {
+    js_sys::Date::new(&ms.into())
+        .to_locale_date_string("default", &wasm_bindgen::JsValue::UNDEFINED)
+        .into()
+}
+
+fn pluralize(count: u64, unit: &str) -> String {
+    if count == 1 {
+        format!("1 {}", unit)
+    } else {
+        format!("{} {}s", count, unit)
+    }
+}
+
+fn due_soon(todos: &[Todo], now: f64, window_ms: f64) -> Vec<String> {
+    todos
+        .iter()
+        .filter(|todo| !todo.completed && !todo.archived)
+        .filter(|todo| {
+            todo.due_at
+                .is_some_and(|due_at| due_at >= now && due_at <= now + window_ms)
+        })
+        .map(|todo| todo.id.clone())
+        .collect()
+}
+
+fn notifications_supported() -> bool {
+    web_sys::window()
+        .is_some_and(|window| js_sys::Reflect::has(&window, &"Notification".into()).unwrap_or(false))
+}
+
+fn notifications_granted() -> bool {
+    notifications_supported()
+        && web_sys::Notification::permission() == web_sys::NotificationPermission::Granted
+}
+
+fn notify_due_soon(todos: &[Todo], notified: &RefCell<HashSet<String>>) {
+    let now = js_sys::Date::now();
+    for id in due_soon(todos, now, REMINDER_WINDOW_MS) {
+        if !notified.borrow_mut().insert(id.clone()) {
+            continue;
+        }
+        let Some(todo) = todos.iter().find(|todo| todo.id == id) else {
+            continue;
+        };
+        let options = web_sys::NotificationOptions::new();
+        options.set_body(&format!("Due {}", humanize_due(todo.due_at.unwrap_or(now), now)));
+        if web_sys::Notification::new_with_options(&todo.title, &options).is_err() {
+            web_sys::console::log_1(&"Failed to show notification".into());
+        }
+    }
+}
+
+fn humanize_due(due_at: f64, now: f64) -> String {
+    let delta = due_at - now;
+    let distance = delta.abs();
+    if distance < ONE_MINUTE_MS {
+        return "just now".to_string();
+    }
+    if delta < 0.0 && distance < ONE_DAY_MS {
+        return "today".to_string();
+    }
+    let amount = if distance < ONE_HOUR_MS {
+        pluralize((distance / ONE_MINUTE_MS) as u64, "minute")
+    } else if distance < ONE_DAY_MS {
+        pluralize((distance / ONE_HOUR_MS) as u64, "hour")
+    } else {
+        pluralize((distance / ONE_DAY_MS) as u64, "day")
+    };
+    if delta > 0.0 {
+        format!("in {}", amount)
+    } else {
+        format!("{} ago", amount)
+    }
+}
+
+fn clear_edit_state(edit_id_handle: &UseStateHandle<Option<String>>) {
+    edit_id_handle.set(None);
+}
+
+fn set_edit_state(edit_id_handle: &UseStateHandle<Option<String>>, id: &str) {
+    edit_id_handle.set(Some(id.to_string()));
+}
+
+fn format_local_timestamp(ms: f64, utc_offset_ms: f64) -> String {
+    let local = ms + utc_offset_ms;
+    let (year, month, day) = civil_from_days(day_number(ms, utc_offset_ms));
+    let minutes = (local.rem_euclid(ONE_DAY_MS) / ONE_MINUTE_MS) as i64;
+    format!("{:04}-{:02}-{:02} {:02}:{:02}", year, month, day, minutes / 60, minutes % 60)
+}
+
+fn format_timestamp(ms: f64) -> String {
+    format_local_timestamp(ms, local_utc_offset_ms(ms))
+}
+
+fn timestamp_tooltip(
+    created_at: Option<f64>,
+    updated_at: Option<f64>,
+    format: impl Fn(f64) -> String,
+) -> String {
+    let show = |ms: Option<f64>| ms.map_or_else(|| "unknown".to_string(), &format);
+    format!("Created: {} / Updated: {}", show(created_at), show(updated_at))
+}
+
+fn display_title(title: &str) -> (String, bool) {
+    if title.trim().is_empty() {
+        ("(no title)".to_string(), true)
+    } else {
+        (title.to_string(), false)
+    }
+}
+
+fn toggle_aria_label(title: &str, completed: bool) -> String {
+    if completed {
+        format!("Mark '{}' incomplete", title)
+    } else {
+        format!("Mark '{}' complete", title)
+    }
+}
+
+fn action_aria_label(action: &str, title: &str) -> String {
+    format!("{} '{}'", action, title)
+}
+
+fn remaining_label(active: usize) -> String {
+    if active == 1 {
+        "1 item left".to_string()
+    } else {
+        format!("{} items left", active)
+    }
+}
+
+fn escape_html(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+fn is_safe_link(url: &str) -> bool {
+    let lower = url.trim().to_ascii_lowercase();
+    ["http://", "https://", "mailto:"]
+        .iter()
+        .any(|scheme| lower.starts_with(scheme))
+}
+
+fn render_markdown_inline(title: &str) -> String {
+    let mut out = String::new();
+    let mut open_links = Vec::new();
+    let mut pending_space = false;
+    for event in Parser::new_ext(title, Options::ENABLE_STRIKETHROUGH) {
+        let fragment = match event {
+            MdEvent::Start(Tag::Strong) => "<strong>".to_string(),
+            MdEvent::End(TagEnd::Strong) => "</strong>".to_string(),
+            MdEvent::Start(Tag::Emphasis) => "<em>".to_string(),
+            MdEvent::End(TagEnd::Emphasis) => "</em>".to_string(),
+            MdEvent::Start(Tag::Strikethrough) => "<del>".to_string(),
+            MdEvent::End(TagEnd::Strikethrough) => "</del>".to_string(),
+            MdEvent::Start(Tag::Link { dest_url, .. }) => {
+                let safe = is_safe_link(&dest_url);
+                open_links.push(safe);
+                if safe {
+                    format!(
+                        "<a href=\"{}\" target=\"_blank\" rel=\"noopener noreferrer\" class=\"text-blue-600 underline\">",
+                        escape_html(&dest_url)
+                    )
+                } else {
+                    String::new()
+                }
+            }
+            MdEvent::End(TagEnd::Link) => {
+                if open_links.pop().unwrap_or(false) {
+                    "</a>".to_string()
+                } else {
+                    String::new()
+                }
+            }
+            MdEvent::Code(code) => format!("<code>{}</code>", escape_html(&code)),
+            MdEvent::Text(text) | MdEvent::Html(text) | MdEvent::InlineHtml(text) => escape_html(&text),
+            MdEvent::SoftBreak | MdEvent::HardBreak => " ".to_string(),
+            MdEvent::End(_) => {
+                pending_space = true;
+                continue;
+            }
+            _ => continue,
+        };
+        if fragment.is_empty() {
+            continue;
+        }
+        if pending_space && !out.is_empty() && !out.ends_with(' ') {
+            out.push(' ');
+        }
+        pending_space = false;
+        out.push_str(&fragment);
+    }
+    out
+}
+
+fn render_title(title: &str, completed: bool, ondblclick: Callback<MouseEvent>) -> Html {
+    let (shown, is_placeholder) = display_title(title);
+    html! {
+        <span {ondblclick} class={classes!("flex-grow", completed.then_some("line-through"), is_placeholder.then_some("italic text-gray-400"))}>
+            if is_placeholder {
+                { shown }
+            } else {
+                { Html::from_html_unchecked(AttrValue::from(render_markdown_inline(&shown))) }
+            }
+        </span>
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct ItemClasses {
+    row: &'static str,
+    title: &'static str,
+    marker: &'static str,
+}
+
+fn item_classes(completed: bool, printing: bool) -> ItemClasses {
+    match (printing, completed) {
+        (true, true) => ItemClasses {
+            row: "flex gap-2 py-1 border-b border-gray-300",
+            title: "text-black",
+            marker: "✓",
+        },
+        (true, false) => ItemClasses {
+            row: "flex gap-2 py-1 border-b border-gray-300",
+            title: "text-black",
+            marker: "☐",
+        },
+        (false, true) => ItemClasses {
+            row: "flex gap-2 p-2 border rounded",
+            title: "line-through text-gray-500",
+            marker: "",
+        },
+        (false, false) => ItemClasses {
+            row: "flex gap-2 p-2 border rounded",
+            title: "",
+            marker: "",
+        },
+    }
+}
+
+fn render_print_view(todos: &[Todo], on_close: Callback<MouseEvent>) -> Html {
+    html! {
+        <div class="p-4 bg-white text-black">
+            <div class="flex items-center justify-between mb-4">
+                <h1 class="text-xl font-bold">{ APP_TITLE }</h1>
+                <button onclick={on_close} class="px-2 py-1 text-sm border rounded print:hidden">
+                    {"Done"}
+                </button>
+            </div>
+            <ul role="list">
+                { for todos.iter().map(|todo| {
+                    let classes = item_classes(todo.completed, true);
+                    html! {
+                        <li role="listitem" class={classes.row}>
+                            <span aria-hidden="true">{ classes.marker }</span>
+                            <span class={classes.title}>{ display_title(&todo.title).0 }</span>
+                        </li>
+                    }
+                })}
+            </ul>
+        </div>
+    }
+}
+
+fn focus_input(input_ref: &NodeRef) {
+    if let Some(input) = input_ref.cast::<HtmlInputElement>() {
+        if input.focus().is_err() {
+            web_sys::console::log_1(&"Failed to focus input".into());
+        }
+    }
+}
+
+fn is_typing_in_field() -> bool {
+    web_sys::window()
+        .and_then(|window| window.document())
+        .and_then(|document| document.active_element())
+        .is_some_and(|element| is_text_entry_tag(&element.tag_name()))
+}
+
+fn is_text_entry_tag(tag_name: &str) -> bool {
+    matches!(tag_name.to_ascii_uppercase().as_str(), "INPUT" | "TEXTAREA" | "SELECT")
+}
+
+fn is_focus_new_todo_shortcut(event: &KeyboardEvent) -> bool {
+    event.key() == "n" && !event.ctrl_key() && !event.meta_key() && !event.alt_key()
+}
+
+fn is_toggle_last_touched_shortcut(event: &KeyboardEvent) -> bool {
+    event.key() == "t" && !event.ctrl_key() && !event.meta_key() && !event.alt_key()
+}
+
+fn resolve_toggle_target(todos: &[Todo], last_touched: Option<&str>) -> Option<String> {
+    let id = last_touched?;
+    todos
+        .iter()
+        .any(|todo| todo.id == id && !todo.archived)
+        .then(|| id.to_string())
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum HistoryStep {
+    Undo,
+    Redo,
+}
+
+fn history_shortcut(key: &str, ctrl_or_meta: bool, shift: bool) -> Option<HistoryStep> {
+    if !ctrl_or_meta || !key.eq_ignore_ascii_case("z") {
+        None
+    } else if shift {
+        Some(HistoryStep::Redo)
+    } else {
+        Some(HistoryStep::Undo)
+    }
+}
+
+fn is_duplicate_shortcut(key: &str, ctrl_or_meta: bool, row_focused: bool, editing: bool) -> bool {
+    key.eq_ignore_ascii_case("d") && ctrl_or_meta && row_focused && !editing
+}
+
+fn focus_and_caret_end(input_ref: &NodeRef) {
+    focus_input(input_ref);
+    if let Some(input) = input_ref.cast::<HtmlInputElement>() {
+        let len = input.value().encode_utf16().count() as u32;
+        if input.set_selection_range(len, len).is_err() {
+            web_sys::console::log_1(&"Failed to move caret to end".into());
+        }
+    }
+}
+
+#[function_component(App)]
+fn app() -> Html {
+    let storage_error = use_state(|| None::<String>);
+    let load_retry_pending = use_mut_ref(|| false);
+    let settings = use_state(load_settings);
+    let profile = use_state(load_profile);
+    let storage_mode = use_state(load_storage_mode);
+    let todos_loaded = use_state(|| false);
+    let remote_settled = use_state(|| backend::BACKEND_URL.is_none());
+    let todos = use_reducer(|| {
+        migrate_legacy_todos();
+        UndoableState::new(match load_todos(&storage_key_for(&profile)) {
+            Ok(todos) => {
+                todos_loaded.set(true);
+                todos
+            }
+            Err(e) => {
+                if should_retry_load(classify_load_error(&e)) {
+                    *load_retry_pending.borrow_mut() = true;
+                } else {
+                    storage_error.set(Some(format!("Failed to load todos: {:?}", e)));
+                }
+                Vec::<Todo>::new()
+            }
+        })
+    });
+
+    let load_snapshot = use_mut_ref(|| todos.present.clone());
+
+    {
+        let todos = todos.clone();
+        let load_snapshot = load_snapshot.clone();
+        let todos_loaded = todos_loaded.clone();
+        let storage_error = storage_error.clone();
+        use_effect_with((), move |_| {
+            let retry = load_retry_pending
+                .replace(false)
+                .then(|| retry_load_after_delay(&todos, &load_snapshot, &todos_loaded, &storage_error));
+            move || drop(retry)
+        });
+    }
+
+    let input_ref = use_node_ref();
+    let new_due_ref = use_node_ref();
+    let category_name_ref = use_node_ref();
+    let category_color_ref = use_node_ref();
+    let edit_id = use_state(|| None::<String>);
+    let show_archived = use_state(|| false);
+    let last_snapshot = use_state(|| None::<(&'static str, Vec<Todo>)>);
+    let export_format = use_state(|| ExportFormat::Json);
+    let sort_mode = use_state(|| initial_sort_mode(settings.sort_mode, settings.sort_on_load));
+    let recent_only = use_state(|| false);
+    let today_only = use_state(|| false);
+    let blocked_only = use_state(|| false);
+    let grouped = use_state(|| false);
+    let due_grouped = use_state(|| false);
+    let completed_last = use_state(|| false);
+    let context_filter = use_state(|| None::<String>);
+    let search_query = use_state(String::new);
+    let weekly_review = use_state(|| None::<(Vec<String>, usize)>);
+    let page = use_state(|| 0usize);
+    let confirm_clear_all = use_state(|| false);
+    let confirm_clear_completed = use_state(|| false);
+    let focused_index = use_state(|| None::<usize>);
+    let show_diagnostics = use_state(diag_requested);
+    let reminders_enabled = use_state(notifications_granted);
+    let notified = use_mut_ref(HashSet::<String>::new);
+
+    {
+        let notified = notified.clone();
+        use_effect_with((*reminders_enabled, todos.present.clone()), move |(enabled, todos)| {
+            let interval = enabled.then(|| {
+                notify_due_soon(todos, &notified);
+                let todos = todos.clone();
+                Interval::new(REMINDER_CHECK_MS, move || notify_due_soon(&todos, &notified))
+            });
+            move || drop(interval)
+        });
+    }
+
+    {
+        let edited_tick = use_state(|| 0_u32);
+        let tick = edited_tick.clone();
+        use_effect_with((todos.present.clone(), *edited_tick), move |(todos, current)| {
+            let current = *current;
+            let timeout = ms_until_edit_badge_expires(todos, js_sys::Date::now(), EDITED_WINDOW_MS)
+                .map(|delay| Timeout::new(delay.ceil() as u32, move || tick.set(current.wrapping_add(1))));
+            move || drop(timeout)
+        });
+    }
+
+    use_effect_with(count_active(&todos), |active| {
+        if let Some(document) = web_sys::window().and_then(|window| window.document()) {
+            document.set_title(&format_doc_title(*active));
+        }
+    });
+    let expanded_group = use_state(|| None::<String>);
+    let route = use_route::<Route>().unwrap_or(Route::Home);
+    let navigator = use_navigator();
+    let filter = filter_for_route(&route);
+    let due_popover = use_state(|| None::<String>);
+    let dragging_id = use_state(|| None::<String>);
+    let flip_before = use_mut_ref(|| None::<HashMap<String, f64>>);
+
+    {
+        let flip_before = flip_before.clone();
+        use_effect_with(todos.present.clone(), move |_| {
+            if let Some(before) = flip_before.borrow_mut().take() {
+                play_flip(&before);
+            }
+        });
+    }
+    let selected = use_state(HashSet::<String>::new);
+    let select_all_ref = use_node_ref();
+    let list_ref = use_node_ref();
+    let last_scroll_save = use_mut_ref(|| 0.0);
+    let expanded = use_state(HashSet::<String>::new);
+    let now = js_sys::Date::now();
+    let edit_input_ref = use_node_ref();
+    let edit_estimate_ref = use_node_ref();
+    let edit_actual_ref = use_node_ref();
+    let new_title_len = use_state(|| 0usize);
+    let review_id = use_state(|| None::<String>);
+    let edit_title_len = use_state(|| 0usize);
+    let edit_error = use_state(|| None::<String>);
+    let subtask_error = use_state(|| None::<(String, String)>);
+    let last_touched = use_state(|| None::<String>);
+
+    {
+        let todos = todos.clone();
+        let edit_id = edit_id.clone();
+        let edit_title_len = edit_title_len.clone();
+        let navigator = navigator.clone();
+        let storage_error = storage_error.clone();
+        use_effect_with(route.clone(), move |route| {
+            match resolve_edit_route(&todos, route) {
+                Some(Ok(id)) => {
+                    if let Some(todo) = todos.iter().find(|todo| todo.id == id) {
+                        edit_title_len.set(title_length(&todo.title));
+                    }
+                    set_edit_state(&edit_id, &id);
+                }
+                Some(Err(e)) => {
+                    storage_error.set(Some(e));
+                    if let Some(navigator) = &navigator {
+                        navigator.replace(&Route::Home);
+                    }
+                }
+                None => {}
+            }
+        });
+    }
+
+    let on_dismiss_error = {
+        let storage_error = storage_error.clone();
+        Callback::from(move |_| storage_error.set(None))
+    };
+
+    let on_retry_save = {
+        let storage_error = storage_error.clone();
+        Callback::from(move |_| retry_pending_write(&storage_error))
+    };
+
+    let on_new_title_input = {
+        let new_title_len = new_title_len.clone();
+        Callback::from(move |e: InputEvent| {
+            let input = e.target_unchecked_into::<HtmlInputElement>();
+            new_title_len.set(title_length(&input.value()));
+            save_draft(&input.value());
+        })
+    };
+
+    {
+        let input_ref = input_ref.clone();
+        let new_title_len = new_title_len.clone();
+        use_effect_with((), move |_| {
+            let draft = load_draft();
+            if let Some(input) = input_ref.cast::<HtmlInputElement>() {
+                input.set_value(&draft);
+                new_title_len.set(title_length(&draft));
+            }
+        });
+    }
+
+    let dispatch_todo = {
+        let todos = todos.clone();
+        let last_snapshot = last_snapshot.clone();
+        let storage_error = storage_error.clone();
+        Callback::from(move |action: TodoAction| {
+            dispatch(&todos, action, &storage_error);
+            clear_snapshot(&last_snapshot);
+        })
+    };
+
+    let on_edit_title_input = {
+        let edit_title_len = edit_title_len.clone();
+        Callback::from(move |e: InputEvent| {
+            let input = e.target_unchecked_into::<HtmlInputElement>();
+            edit_title_len.set(title_length(&input.value()));
+        })
+    };
+
+    let on_submit = {
+        let todos = todos.clone();
+        let input_ref = input_ref.clone();
+        let new_due_ref = new_due_ref.clone();
+        let new_title_len = new_title_len.clone();
+        let settings = settings.clone();
+        let last_snapshot = last_snapshot.clone();
+        let storage_error = storage_error.clone();
+        Callback::from(move |e: SubmitEvent| {
+            e.prevent_default();
+            if let Some(input) = input_ref.cast::<HtmlInputElement>() {
+                if let Some(title) = resolve_submit_title(&input.value(), settings.allow_placeholder) {
+                    let now = js_sys::Date::now();
+                    let due_input = new_due_ref.cast::<HtmlInputElement>();
+                    let due_at = due_input
+                        .as_ref()
+                        .and_then(|due_input| parse_date_input(&due_input.value(), local_utc_offset_ms(now)));
+                    let auto_capitalize = settings.auto_capitalize;
+                    let action = TodoAction::Add { title, auto_capitalize, due_at, now };
+                    if let Err(e) = try_dispatch(&todos, action, &storage_error) {
+                        storage_error.set(Some(e));
+                        return;
+                    }
+                    clear_snapshot(&last_snapshot);
+                    clear_input(&input);
+                    if let Some(due_input) = &due_input {
+                        clear_input(due_input);
+                    }
+                    new_title_len.set(0);
+                    save_draft("");
+                }
+            }
+        })
+    };
+
+    let on_toggle_auto_capitalize = {
+        let settings = settings.clone();
+        let storage_error = storage_error.clone();
+        Callback::from(move |_| {
+            let new_settings = Settings {
+                auto_capitalize: !settings.auto_capitalize,
+                ..(*settings).clone()
+            };
+            update_settings(&settings, new_settings, &storage_error);
+        })
+    };
+
+    let on_toggle_placeholder = {
+        let settings = settings.clone();
+        let storage_error = storage_error.clone();
+        Callback::from(move |_| {
+            let new_settings = Settings {
+                allow_placeholder: !settings.allow_placeholder,
+                ..(*settings).clone()
+            };
+            update_settings(&settings, new_settings, &storage_error);
+        })
+    };
+
+    let on_capacity_change = {
+        let settings = settings.clone();
+        let storage_error = storage_error.clone();
+        Callback::from(move |e: Event| {
+            let input = e.target_unchecked_into::<HtmlInputElement>();
+            let new_settings = Settings {
+                capacity: parse_points(&input.value()),
+                ..(*settings).clone()
+            };
+            update_settings(&settings, new_settings, &storage_error);
+        })
+    };
+
+    let on_recurrence_change = dispatch_todo.reform(|(id, value): (String, String)| {
+        let recurrence = Recurrence::from_value(&value);
+        TodoAction::SetRecurrence { id, recurrence }
+    });
+
+    let on_category_change = {
+        let dispatch_todo = dispatch_todo.clone();
+        let settings = settings.clone();
+        Callback::from(move |(id, name): (String, String)| {
+            let category = settings
+                .categories
+                .iter()
+                .find(|category| category.name == name)
+                .cloned();
+            dispatch_todo.emit(TodoAction::SetCategory { id, category });
+        })
+    };
+
+    let on_add_category = {
+        let settings = settings.clone();
+        let category_name_ref = category_name_ref.clone();
+        let category_color_ref = category_color_ref.clone();
+        let storage_error = storage_error.clone();
+        Callback::from(move |e: SubmitEvent| {
+            e.prevent_default();
+            let (Some(name_input), Some(color_input)) = (
+                category_name_ref.cast::<HtmlInputElement>(),
+                category_color_ref.cast::<HtmlInputElement>(),
+            ) else {
+                return;
+            };
+            let new_settings = Settings {
+                categories: add_category(&settings.categories, &name_input.value(), &color_input.value()),
+                ..(*settings).clone()
+            };
+            update_settings(&settings, new_settings, &storage_error);
+            clear_input(&name_input);
+        })
+    };
+
+    let on_points_change = dispatch_todo.reform(|(id, value): (String, String)| {
+        let points = parse_points(&value);
+        TodoAction::SetPoints { id, points }
+    });
+
+    let on_blocked_reason_change =
+        dispatch_todo.reform(|(id, reason): (String, String)| TodoAction::SetBlockedReason { id, reason });
+
+    let on_delete = {
+        let todos = todos.clone();
+        let last_snapshot = last_snapshot.clone();
+        let storage_error = storage_error.clone();
+        Callback::from(move |id: String| {
+            update_todos_destructive(
+                &todos,
+                TodoAction::Delete { id },
+                "Todo deleted",
+                &last_snapshot,
+                &storage_error,
+            );
+        })
+    };
+
+    let on_select = {
+        let selected = selected.clone();
+        Callback::from(move |id: String| selected.set(toggle_selected(&selected, &id)))
+    };
+
+    let on_select_all = {
+        let selected = selected.clone();
+        Callback::from(move |(select, visible_ids): (bool, HashSet<String>)| {
+            selected.set(set_all_selected(&selected, &visible_ids, select))
+        })
+    };
+
+    let on_delete_selected = {
+        let todos = todos.clone();
+        let selected = selected.clone();
+        let last_snapshot = last_snapshot.clone();
+        let storage_error = storage_error.clone();
+        Callback::from(move |_| {
+            update_todos_destructive(
+                &todos,
+                TodoAction::DeleteMany {
+                    ids: (*selected).clone(),
+                },
+                "Selected todos deleted",
+                &last_snapshot,
+                &storage_error,
+            );
+            selected.set(HashSet::new());
+        })
+    };
+
+    let on_clear_tags_selected = {
+        let dispatch_todo = dispatch_todo.clone();
+        let selected = selected.clone();
+        Callback::from(move |_| {
+            let ids = (*selected).clone();
+            dispatch_todo.emit(TodoAction::ClearTags { ids });
+        })
+    };
+
+    let on_move_selected_to_edge = {
+        let dispatch_todo = dispatch_todo.clone();
+        let selected = selected.clone();
+        let flip_before = flip_before.clone();
+        Callback::from(move |to_top: bool| {
+            capture_row_positions(&flip_before);
+            let ids = (*selected).clone();
+            dispatch_todo.emit(TodoAction::MoveToEdge { ids, to_top });
+        })
+    };
+
+    let on_undo = {
+        let last_snapshot = last_snapshot.clone();
+        let dispatch_todo = dispatch_todo.clone();
+        Callback::from(move |_| {
+            if let Some((_, snapshot)) = (*last_snapshot).clone() {
+                dispatch_todo.emit(TodoAction::Replace { todos: snapshot });
+            }
+        })
+    };
+
+    let on_archive = dispatch_todo.reform(|id: String| TodoAction::Archive { id });
+
+    let on_restore = dispatch_todo.reform(|id: String| TodoAction::Restore { id });
+
+    let on_history_undo = {
+        let todos = todos.clone();
+        let storage_error = storage_error.clone();
+        Callback::from(move |_: ()| {
+            if let Some(previous) = todos.past.last() {
+                persist_todos(&todos, previous, &storage_error);
+                todos.dispatch(HistoryAction::Undo);
+            }
+        })
+    };
+
+    let on_history_redo = {
+        let todos = todos.clone();
+        let storage_error = storage_error.clone();
+        Callback::from(move |_: ()| {
+            if let Some(next) = todos.future.last() {
+                persist_todos(&todos, next, &storage_error);
+                todos.dispatch(HistoryAction::Redo);
+            }
+        })
+    };
+
+    let on_export_format_change = {
+        let export_format = export_format.clone();
+        Callback::from(move |e: Event| {
+            let select = e.target_unchecked_into::<HtmlSelectElement>();
+            if let Some(format) = ExportFormat::from_value(&select.value()) {
+                export_format.set(format);
+            }
+        })
+    };
+
+    let on_export_download = {
+        let todos = todos.clone();
+        let export_format = export_format.clone();
+        let storage_error = storage_error.clone();
+        Callback::from(move |_| download_export(&todos, *export_format, &storage_error))
+    };
+
+    let on_export_csv = {
+        let todos = todos.clone();
+        let storage_error = storage_error.clone();
+        Callback::from(move |_| download_export(&todos, ExportFormat::Csv, &storage_error))
+    };
+
+    let on_copy_list = {
+        let todos = todos.clone();
+        let storage_error = storage_error.clone();
+        Callback::from(move |_| {
+            let unarchived: Vec<Todo> = todos.iter().filter(|todo| !todo.archived).cloned().collect();
+            copy_to_clipboard(todos_to_text(&unarchived), &storage_error)
+        })
+    };
+
+    let on_export_tag_report = {
+        let todos = todos.clone();
+        let storage_error = storage_error.clone();
+        Callback::from(move |_| {
+            let contents = export_tag_report_csv(&tag_report(&todos));
+            if let Err(e) = download_file("tag-report.csv", &contents, ExportFormat::Csv.mime_type()) {
+                storage_error.set(Some(format!("Export error: {}", e)));
+            }
+        })
+    };
+
+    let on_export_copy = {
+        let todos = todos.clone();
+        let export_format = export_format.clone();
+        let storage_error = storage_error.clone();
+        Callback::from(move |_| copy_to_clipboard(render_export(&todos, *export_format), &storage_error))
+    };
+
+    let on_import = {
+        let load_snapshot = load_snapshot.clone();
+        let dispatch_todo = dispatch_todo.clone();
+        let storage_error = storage_error.clone();
+        Callback::from(move |e: Event| {
+            let input = e.target_unchecked_into::<HtmlInputElement>();
+            let Some(file) = input.files().and_then(|files| files.get(0)) else {
+                return;
+            };
+            input.set_value("");
+            let filename = file.name();
+            let promise = file.text();
+            let load_snapshot = load_snapshot.clone();
+            let dispatch_todo = dispatch_todo.clone();
+            let storage_error = storage_error.clone();
+            spawn_local(async move {
+                let text = match JsFuture::from(promise).await {
+                    Ok(text) => text.as_string().unwrap_or_default(),
+                    Err(e) => {
+                        storage_error.set(Some(format!("Import error: {:?}", e)));
+                        return;
+                    }
+                };
+                match parse_import(&filename, &text) {
+                    Ok(imported) => {
+                        *load_snapshot.borrow_mut() = imported.clone();
+                        dispatch_todo.emit(TodoAction::Replace { todos: imported });
+                    }
+                    Err(e) => storage_error.set(Some(format!("Import error: {}", e))),
+                }
+            });
+        })
+    };
+
+    let on_profile_change = {
+        let profile = profile.clone();
+        let todos = todos.clone();
+        let load_snapshot = load_snapshot.clone();
+        let last_snapshot = last_snapshot.clone();
+        let todos_loaded = todos_loaded.clone();
+        let storage_error = storage_error.clone();
+        Callback::from(move |name: String| {
+            let Some(name) = normalize_profile(&name) else {
+                return;
+            };
+            save_profile(&name, &storage_error);
+            match load_todos(&storage_key_for(&name)) {
+                Ok(loaded) => {
+                    *load_snapshot.borrow_mut() = loaded.clone();
+                    reset_todos_state(&todos, loaded);
+                    todos_loaded.set(true);
+                    storage_error.set(None);
+                }
+                Err(e) => {
+                    reset_todos_state(&todos, Vec::new());
+                    todos_loaded.set(false);
+                    storage_error.set(Some(format!("Failed to load todos: {:?}", e)));
+                }
+            }
+            clear_snapshot(&last_snapshot);
+            profile.set(name);
+        })
+    };
+
+    let on_reload_conflict = {
+        let todos = todos.clone();
+        let load_snapshot = load_snapshot.clone();
+        let last_snapshot = last_snapshot.clone();
+        let todos_loaded = todos_loaded.clone();
+        let storage_error = storage_error.clone();
+        Callback::from(move |_| {
+            resolve_conflict();
+            match load_todos(&todos_key()) {
+                Ok(loaded) => {
+                    *load_snapshot.borrow_mut() = loaded.clone();
+                    reset_todos_state(&todos, loaded);
+                    todos_loaded.set(true);
+                    storage_error.set(None);
+                }
+                Err(e) => storage_error.set(Some(format!("Failed to load todos: {:?}", e))),
+            }
+            clear_snapshot(&last_snapshot);
+        })
+    };
+
+    let on_overwrite_conflict = {
+        let todos = todos.clone();
+        let storage_error = storage_error.clone();
+        Callback::from(move |_| {
+            resolve_conflict();
+            sync_read_revision(persistent_store().as_ref(), &todos_key());
+            storage_error.set(None);
+            save_todos_to_storage_with_error(&todos_key(), &todos, &storage_error);
+        })
+    };
+
+    let on_storage_mode_change = {
+        let storage_mode = storage_mode.clone();
+        let todos = todos.clone();
+        let storage_error = storage_error.clone();
+        Callback::from(move |value: String| {
+            let Some(mode) = StorageMode::from_value(&value) else {
+                return;
+            };
+            save_storage_mode(mode, &storage_error);
+            sync_read_revision(persistent_store().as_ref(), &todos_key());
+            save_todos_to_storage_with_error(&todos_key(), &todos, &storage_error);
+            if mode == StorageMode::Session {
+                LocalStorage::delete(todos_key());
+            }
+            storage_mode.set(mode);
+        })
+    };
+
+    let on_remove_duplicates = {
+        let todos = todos.clone();
+        let dispatch_todo = dispatch_todo.clone();
+        Callback::from(move |_| {
+            if dedupe_todos(&todos).len() != todos.len() {
+                dispatch_todo.emit(TodoAction::Dedupe);
+            }
+        })
+    };
+
+    let on_clear_all = {
+        let todos = todos.clone();
+        let confirm_clear_all = confirm_clear_all.clone();
+        let last_snapshot = last_snapshot.clone();
+        let storage_error = storage_error.clone();
+        Callback::from(move |_| {
+            let (confirming, cleared) = advance_clear_all(*confirm_clear_all, &todos);
+            confirm_clear_all.set(confirming);
+            if cleared.is_some() {
+                update_todos_destructive(&todos, TodoAction::ClearAll, "All todos cleared", &last_snapshot, &storage_error);
+            }
+        })
+    };
+
+    let on_clear_completed = {
+        let todos = todos.clone();
+        let confirm_clear_completed = confirm_clear_completed.clone();
+        let last_snapshot = last_snapshot.clone();
+        let storage_error = storage_error.clone();
+        Callback::from(move |_| {
+            let (confirming, cleared) = advance_clear_completed(*confirm_clear_completed, &todos);
+            confirm_clear_completed.set(confirming);
+            if cleared.is_some() {
+                update_todos_destructive(
+                    &todos,
+                    TodoAction::ClearCompleted,
+                    "Completed todos cleared",
+                    &last_snapshot,
+                    &storage_error,
+                );
+            }
+        })
+    };
+
+    let on_cancel_clear_completed = {
+        let confirm_clear_completed = confirm_clear_completed.clone();
+        Callback::from(move |_| confirm_clear_completed.set(false))
+    };
+
+    let on_cancel_clear_all = {
+        let confirm_clear_all = confirm_clear_all.clone();
+        Callback::from(move |_| confirm_clear_all.set(false))
+    };
+
+    let on_revert = {
+        let load_snapshot = load_snapshot.clone();
+        let dispatch_todo = dispatch_todo.clone();
+        Callback::from(move |_| {
+            let snapshot = load_snapshot.borrow().clone();
+            dispatch_todo.emit(TodoAction::Replace { todos: snapshot });
+        })
+    };
+
+    let on_toggle_archived = {
+        let show_archived = show_archived.clone();
+        Callback::from(move |_| show_archived.set(!*show_archived))
+    };
+
+    let on_snooze = dispatch_todo.reform(|id: String| TodoAction::Snooze { id });
+
+    let on_due_action = {
+        let dispatch_todo = dispatch_todo.clone();
+        let due_popover = due_popover.clone();
+        Callback::from(move |(id, action): (String, DueAction)| {
+            let now = js_sys::Date::now();
+            let utc_offset_ms = local_utc_offset_ms(now);
+            dispatch_todo.emit(TodoAction::Due { id, action, now, utc_offset_ms });
+            due_popover.set(None);
+        })
+    };
+
+    let on_open_due_popover = {
+        let due_popover = due_popover.clone();
+        Callback::from(move |(e, id): (MouseEvent, String)| {
+            e.stop_propagation();
+            due_popover.set(Some(id));
+        })
+    };
+
+    let printing = use_state(|| false);
+
+    {
+        let printing = printing.clone();
+        use_effect_with(*printing, move |is_printing| {
+            let listener = is_printing.then(web_sys::window).flatten().map(|window| {
+                let listener = EventListener::new(&window, "afterprint", move |_| printing.set(false));
+                if window.print().is_err() {
+                    web_sys::console::log_1(&"Failed to open print dialog".into());
+                }
+                listener
+            });
+            move || drop(listener)
+        });
+    }
+
+    let on_print = {
+        let printing = printing.clone();
+        Callback::from(move |_| printing.set(true))
+    };
+
+    let on_close_print = {
+        let printing = printing.clone();
+        Callback::from(move |_| printing.set(false))
+    };
+
+    {
+        let due_popover = due_popover.clone();
+        use_effect_with(due_popover.is_some(), move |is_open| {
+            let listener = is_open
+                .then(web_sys::window)
+                .flatten()
+                .map(|window| {
+                    EventListener::new(&window, "click", move |_| due_popover.set(None))
+                });
+            move || drop(listener)
+        });
+    }
+
+    let on_toggle = {
+        let todos = todos.clone();
+        let dispatch_todo = dispatch_todo.clone();
+        let review_id = review_id.clone();
+        let last_touched = last_touched.clone();
+        Callback::from(move |id: String| {
+            last_touched.set(Some(id.clone()));
+            if todos.iter().any(|todo| todo.id == id && !todo.completed && !can_complete(todo)) {
+                review_id.set(Some(id));
+                return;
+            }
+            let now = js_sys::Date::now();
+            let utc_offset_ms = local_utc_offset_ms(now);
+            dispatch_todo.emit(TodoAction::Toggle { id, now, utc_offset_ms });
+        })
+    };
+
+    let on_enable_reminders = {
+        let reminders_enabled = reminders_enabled.clone();
+        let storage_error = storage_error.clone();
+        Callback::from(move |_| {
+            let promise = match web_sys::Notification::request_permission() {
+                Ok(promise) => promise,
+                Err(e) => {
+                    storage_error.set(Some(format!("Notification error: {:?}", e)));
+                    return;
+                }
+            };
+            let reminders_enabled = reminders_enabled.clone();
+            let storage_error = storage_error.clone();
+            spawn_local(async move {
+                match JsFuture::from(promise).await {
+                    Ok(permission) if permission.as_string().as_deref() == Some("granted") => {
+                        reminders_enabled.set(true)
+                    }
+                    Ok(_) => storage_error.set(Some("Notifications were not allowed".to_string())),
+                    Err(e) => storage_error.set(Some(format!("Notification error: {:?}", e))),
+                }
+            });
+        })
+    };
+
+    let on_start_weekly_review = {
+        let todos = todos.clone();
+        let weekly_review = weekly_review.clone();
+        Callback::from(move |_| {
+            let queue = review_queue(&todos);
+            weekly_review.set((!queue.is_empty()).then_some((queue, 0)));
+        })
+    };
+
+    let on_weekly_review_step = {
+        let weekly_review = weekly_review.clone();
+        let on_toggle = on_toggle.clone();
+        let on_delete = on_delete.clone();
+        let on_snooze = on_snooze.clone();
+        Callback::from(move |step: ReviewStep| {
+            let Some((queue, index)) = (*weekly_review).clone() else {
+                return;
+            };
+            if let Some(id) = queue.get(index) {
+                match step {
+                    ReviewStep::Keep => {}
+                    ReviewStep::Complete => on_toggle.emit(id.clone()),
+                    ReviewStep::Delete => on_delete.emit(id.clone()),
+                    ReviewStep::Snooze => on_snooze.emit(id.clone()),
+                }
+            }
+            weekly_review.set((index + 1 < queue.len()).then_some((queue, index + 1)));
+        })
+    };
+
+    let on_close_weekly_review = {
+        let weekly_review = weekly_review.clone();
+        Callback::from(move |_| weekly_review.set(None))
+    };
+
+    let on_confirm_review = {
+        let dispatch_todo = dispatch_todo.clone();
+        let review_id = review_id.clone();
+        Callback::from(move |_| {
+            if let Some(id) = (*review_id).clone() {
+                let now = js_sys::Date::now();
+                let utc_offset_ms = local_utc_offset_ms(now);
+                dispatch_todo.emit(TodoAction::ConfirmReview { id, now, utc_offset_ms });
+            }
+            review_id.set(None);
+        })
+    };
+
+    let on_cancel_review = {
+        let review_id = review_id.clone();
+        Callback::from(move |_| review_id.set(None))
+    };
+
+    let on_add_subtask = {
+        let todos = todos.clone();
+        let subtask_error = subtask_error.clone();
+        let last_snapshot = last_snapshot.clone();
+        let storage_error = storage_error.clone();
+        Callback::from(move |(id, input): (String, HtmlInputElement)| {
+            let action = TodoAction::AddSubtask {
+                id: id.clone(),
+                title: input.value(),
+            };
+            match try_dispatch(&todos, action, &storage_error) {
+                Ok(()) => {
+                    clear_snapshot(&last_snapshot);
+                    clear_input(&input);
+                    subtask_error.set(None);
+                }
+                Err(e) => subtask_error.set(Some((id, e))),
+            }
+        })
+    };
+
+    let on_complete_visible = dispatch_todo.reform(|visible_ids: HashSet<String>| {
+        let now = js_sys::Date::now();
+        let utc_offset_ms = local_utc_offset_ms(now);
+        TodoAction::CompleteVisible {
+            ids: visible_ids,
+            now,
+            utc_offset_ms,
+        }
+    });
+
+    let on_toggle_subtask = dispatch_todo.reform(|(id, subtask_id): (String, String)| {
+        let now = js_sys::Date::now();
+        let utc_offset_ms = local_utc_offset_ms(now);
+        TodoAction::ToggleSubtask {
+            id,
+            subtask_id,
+            now,
+            utc_offset_ms,
+        }
+    });
+
+    let on_delete_subtask =
+        dispatch_todo.reform(|(id, subtask_id): (String, String)| TodoAction::DeleteSubtask { id, subtask_id });
+
+    let on_toggle_review = dispatch_todo.reform(|id: String| TodoAction::ToggleReview { id });
+
+    let on_duplicate = dispatch_todo.reform(|id: String| TodoAction::Duplicate {
+        id,
+        now: js_sys::Date::now(),
+    });
+
+    let on_drag_start = {
+        let dragging_id = dragging_id.clone();
+        Callback::from(move |id: String| dragging_id.set(Some(id)))
+    };
+
+    let on_drop = {
+        let todos = todos.clone();
+        let flip_before = flip_before.clone();
+        let dragging_id = dragging_id.clone();
+        let dispatch_todo = dispatch_todo.clone();
+        Callback::from(move |target_id: String| {
+            let ordered = sort_by_order(&todos);
+            let position = |id: &str| ordered.iter().position(|todo| todo.id == id);
+            if let Some((from, to)) = dragging_id
+                .as_deref()
+                .filter(|id| validate_reorder(&todos, id, &target_id))
+                .and_then(|id| position(id).zip(position(&target_id)))
+            {
+                capture_row_positions(&flip_before);
+                dispatch_todo.emit(TodoAction::Reorder { from, to });
+            }
+            dragging_id.set(None);
+        })
+    };
+
+    let on_star = dispatch_todo.reform(|id: String| TodoAction::Star { id });
+
+    let on_pin = dispatch_todo.reform(|id: String| TodoAction::Pin { id });
+
+    let on_cycle_priority = dispatch_todo.reform(|(id, priority): (String, Priority)| {
+        let priority = priority.next();
+        TodoAction::SetPriority { id, priority }
+    });
+
+    let on_sort_mode_change = {
+        let sort_mode = sort_mode.clone();
+        let settings = settings.clone();
+        let storage_error = storage_error.clone();
+        Callback::from(move |e: Event| {
+            let select = e.target_unchecked_into::<HtmlSelectElement>();
+            if let Some(mode) = SortMode::from_value(&select.value()) {
+                sort_mode.set(mode);
+                let new_settings = Settings {
+                    sort_mode: mode,
+                    ..(*settings).clone()
+                };
+                update_settings(&settings, new_settings, &storage_error);
+            }
+        })
+    };
+
+    let on_toggle_sort_on_load = {
+        let settings = settings.clone();
+        let storage_error = storage_error.clone();
+        Callback::from(move |_| {
+            let new_settings = Settings {
+                sort_on_load: !settings.sort_on_load,
+                ..(*settings).clone()
+            };
+            update_settings(&settings, new_settings, &storage_error);
+        })
+    };
+
+    let on_filter = {
+        let navigator = navigator.clone();
+        let storage_error = storage_error.clone();
+        Callback::from(move |new_filter: Filter| {
+            save_filter(new_filter, &storage_error);
+            if let Some(navigator) = &navigator {
+                navigator.push(&route_for_filter(new_filter));
+            }
+        })
+    };
+
+    {
+        let todos = todos.clone();
+        let todos_loaded = todos_loaded.clone();
+        let remote_settled = remote_settled.clone();
+        let storage_error = storage_error.clone();
+        use_effect_with((*profile).clone(), move |profile| {
+            let cancelled = std::rc::Rc::new(Cell::new(false));
+            let listener = backend::BACKEND_URL.map(|base| {
+                let sync_error = storage_error.clone();
+                let key = storage_key_for(profile);
+                let queue_key = backend::pending_ops_key(profile);
+                let cancelled = cancelled.clone();
+                spawn_local(async move {
+                    match backend::fetch_todos(base).await {
+                        _ if cancelled.get() => {}
+                        Ok(remote) => {
+                            let merged = backend::apply_ops(&remote, &backend::load_pending_ops(&queue_key));
+                            save_todos_to_storage_with_error(&key, &merged, &sync_error);
+                            update_todos_state(&todos, merged);
+                            todos_loaded.set(true);
+                            remote_settled.set(true);
+                            flush_pending_ops(&sync_error);
+                        }
+                        Err(e) => {
+                            sync_error.set(Some(format!("Sync error: {} (using local copy)", e)));
+                            remote_settled.set(true);
+                        }
+                    }
+                });
+                web_sys::window().map(|window| {
+                    EventListener::new(&window, "online", move |_| flush_pending_ops(&storage_error))
+                })
+            });
+            move || {
+                cancelled.set(true);
+                drop(listener)
+            }
+        });
+    }
+
+    {
+        let todos = todos.clone();
+        let storage_error = storage_error.clone();
+        use_effect_with(*todos_loaded && *remote_settled, move |ready| {
+            let now = js_sys::Date::now();
+            let utc_offset_ms = local_utc_offset_ms(now);
+            let today = day_number(now, utc_offset_ms);
+            if *ready && should_rollover(LocalStorage::get(LAST_ROLLOVER_KEY).ok(), today) {
+                let persisted = match apply_action(&todos, TodoAction::RolloverOverdue { now, utc_offset_ms }) {
+                    Ok(Some((old_todos, new_todos))) => persist_todos(&old_todos, &new_todos, &storage_error),
+                    Ok(None) => true,
+                    Err(e) => {
+                        storage_error.set(Some(e));
+                        false
+                    }
+                };
+                if persisted {
+                    if let Err(e) = LocalStorage::set(LAST_ROLLOVER_KEY, today) {
+                        storage_error.set(Some(format!("Storage error: {:?}", e)));
+                    }
+                }
+            }
+        });
+    }
+
+    {
+        let navigator = navigator.clone();
+        let route = route.clone();
+        use_effect_with((), move |_| {
+            let stored = load_filter();
+            if route == Route::Home && stored != Filter::All {
+                if let Some(navigator) = navigator {
+                    navigator.replace(&route_for_filter(stored));
+                }
+            }
+        });
+    }
+
+    let on_toggle_recent = {
+        let recent_only = recent_only.clone();
+        Callback::from(move |_| recent_only.set(!*recent_only))
+    };
+
+    let on_toggle_today = {
+        let today_only = today_only.clone();
+        Callback::from(move |_| today_only.set(!*today_only))
+    };
+
+    let on_toggle_expanded = {
+        let expanded = expanded.clone();
+        Callback::from(move |id: String| expanded.set(toggle_expanded(&expanded, &id)))
+    };
+
+    {
+        let list_ref = list_ref.clone();
+        use_effect_with((), move |_| {
+            let saved = LocalStorage::get::<i32>(SCROLL_KEY);
+            if let (Some(list), Ok(saved)) = (list_ref.cast::<web_sys::Element>(), saved) {
+                let top = clamp_scroll(saved, list.scroll_height(), list.client_height());
+                list.set_scroll_top(top);
+            }
+        });
+    }
+
+    let on_list_scroll = {
+        let list_ref = list_ref.clone();
+        let last_scroll_save = last_scroll_save.clone();
+        Callback::from(move |_: Event| {
+            let now = js_sys::Date::now();
+            if now - *last_scroll_save.borrow() < SCROLL_SAVE_THROTTLE_MS {
+                return;
+            }
+            *last_scroll_save.borrow_mut() = now;
+            if let Some(list) = list_ref.cast::<web_sys::Element>() {
+                if LocalStorage::set(SCROLL_KEY, list.scroll_top()).is_err() {
+                    web_sys::console::log_1(&"Failed to save scroll position".into());
+                }
+            }
+        })
+    };
+
+    let on_page = {
+        let page = page.clone();
+        Callback::from(move |new_page: usize| page.set(new_page))
+    };
+
+    let on_context_filter_change = {
+        let context_filter = context_filter.clone();
+        Callback::from(move |e: Event| {
+            let select = e.target_unchecked_into::<HtmlSelectElement>();
+            let value = select.value();
+            context_filter.set((!value.is_empty()).then_some(value));
+        })
+    };
+
+    let on_search_input = {
+        let search_query = search_query.clone();
+        Callback::from(move |e: InputEvent| {
+            let input = e.target_unchecked_into::<HtmlInputElement>();
+            search_query.set(input.value());
+        })
+    };
+
+    let on_toggle_completed_last = {
+        let completed_last = completed_last.clone();
+        Callback::from(move |_| completed_last.set(!*completed_last))
+    };
+
+    let on_toggle_grouped = {
+        let grouped = grouped.clone();
+        Callback::from(move |_| grouped.set(!*grouped))
+    };
+
+    let on_toggle_due_grouped = {
+        let due_grouped = due_grouped.clone();
+        Callback::from(move |_| due_grouped.set(!*due_grouped))
+    };
+
+    let on_toggle_group = {
+        let expanded_group = expanded_group.clone();
+        Callback::from(move |key: String| {
+            expanded_group.set(toggle_accordion(expanded_group.as_deref(), &key))
+        })
+    };
+
+    let on_toggle_blocked_only = {
+        let blocked_only = blocked_only.clone();
+        Callback::from(move |_| blocked_only.set(!*blocked_only))
+    };
+
+    let on_edit = {
+        let todos = todos.clone();
+        let edit_id = edit_id.clone();
+        let edit_input_ref = edit_input_ref.clone();
+        let edit_title_len = edit_title_len.clone();
+        let edit_error = edit_error.clone();
+        Callback::from(move |id: String| {
+            if let Some(todo) = todos.iter().find(|todo| todo.id == id) {
+                edit_title_len.set(title_length(&todo.title));
+            }
+            edit_error.set(None);
+            set_edit_state(&edit_id, &id);
+            focus_and_caret_end(&edit_input_ref);
+        })
+    };
+
+    {
+        let input_ref = input_ref.clone();
+        use_effect_with((), move |_| {
+            let listener = web_sys::window().map(|window| {
+                EventListener::new(&window, "keydown", move |event| {
+                    let event = event.unchecked_ref::<KeyboardEvent>();
+                    if is_focus_new_todo_shortcut(event) && !is_typing_in_field() {
+                        event.prevent_default();
+                        focus_input(&input_ref);
+                    }
+                })
+            });
+            move || drop(listener)
+        });
+    }
+
+    {
+        let on_toggle = on_toggle.clone();
+        use_effect_with(
+            ((*last_touched).clone(), todos.present.clone()),
+            move |(last_touched, todos)| {
+                let target = resolve_toggle_target(todos, last_touched.as_deref());
+                let listener = web_sys::window().zip(target).map(|(window, id)| {
+                    EventListener::new(&window, "keydown", move |event| {
+                        let event = event.unchecked_ref::<KeyboardEvent>();
+                        if is_toggle_last_touched_shortcut(event) && !is_typing_in_field() {
+                            event.prevent_default();
+                            on_toggle.emit(id.clone());
+                        }
+                    })
+                });
+                move || drop(listener)
+            },
+        );
+    }
+
+    {
+        let history_steps = use_mut_ref(|| (on_history_undo.clone(), on_history_redo.clone()));
+        *history_steps.borrow_mut() = (on_history_undo.clone(), on_history_redo.clone());
+        use_effect_with((), move |_| {
+            let listener = web_sys::window().map(|window| {
+                EventListener::new(&window, "keydown", move |event| {
+                    let event = event.unchecked_ref::<KeyboardEvent>();
+                    if is_typing_in_field() {
+                        return;
+                    }
+                    let ctrl_or_meta = event.ctrl_key() || event.meta_key();
+                    let (on_undo, on_redo) = history_steps.borrow().clone();
+                    match history_shortcut(&event.key(), ctrl_or_meta, event.shift_key()) {
+                        Some(HistoryStep::Undo) => on_undo.emit(()),
+                        Some(HistoryStep::Redo) => on_redo.emit(()),
+                        None => return,
+                    }
+                    event.prevent_default();
+                })
+            });
+            move || drop(listener)
+        });
+    }
+
+    {
+        let edit_input_ref = edit_input_ref.clone();
+        use_effect_with((*edit_id).clone(), move |_| {
+            focus_and_caret_end(&edit_input_ref);
+        });
+    }
+
+    let on_update = {
+        let todos = todos.clone();
+        let edit_id = edit_id.clone();
+        let edit_error = edit_error.clone();
+        let last_touched = last_touched.clone();
+        let edit_input_ref = edit_input_ref.clone();
+        let edit_estimate_ref = edit_estimate_ref.clone();
+        let edit_actual_ref = edit_actual_ref.clone();
+        let dispatch_todo = dispatch_todo.clone();
+        Callback::from(move |id: String| {
+            if let Some(input) = edit_input_ref.cast::<HtmlInputElement>() {
+                let title = read_input_title(&input);
+                match validate_edit(&todos, &id, &title) {
+                    Ok(()) => {
+                        last_touched.set(Some(id.clone()));
+                        let now = js_sys::Date::now();
+                        let minutes = |input_ref: &NodeRef| {
+                            input_ref
+                                .cast::<HtmlInputElement>()
+                                .and_then(|input| parse_points(&input.value()))
+                        };
+                        let action = TodoAction::Batch(vec![
+                            TodoAction::SetMinutes {
+                                id: id.clone(),
+                                estimate_minutes: minutes(&edit_estimate_ref),
+                                actual_minutes: minutes(&edit_actual_ref),
+                            },
+                            TodoAction::UpdateTitle { id, title, now },
+                        ]);
+                        dispatch_todo.emit(action);
+                        clear_edit_state(&edit_id);
+                        edit_error.set(None);
+                    }
+                    Err(e) => edit_error.set(Some(e)),
+                }
+            }
+        })
+    };
+
+    let on_cancel = {
+        let edit_id = edit_id.clone();
+        let edit_error = edit_error.clone();
+        Callback::from(move |_| {
+            clear_edit_state(&edit_id);
+            edit_error.set(None);
+        })
+    };
+
+    let visible_todos = {
+        let unarchived: Vec<Todo> =
+            sort_by_order(&todos).into_iter().filter(|todo| !todo.archived).collect();
+        let filtered = filter_todos(&unarchived, filter);
+        let filtered = if *recent_only {
+            completed_within(&filtered, RECENT_WINDOW_MS, now)
+        } else {
+            filtered
+        };
+        let filtered = if *today_only {
+            completed_today(&filtered, now, local_utc_offset_ms(now))
+        } else {
+            filtered
+        };
+        let filtered = if *blocked_only {
+            filter_blocked(&filtered)
+        } else {
+            filtered
+        };
+        let filtered = match context_filter.as_deref() {
+            Some(context) => filter_by_context(&filtered, context),
+            None => filtered,
+        };
+        let sorted = sort_todos(&filtered, *sort_mode, now);
+        let sorted = if *completed_last {
+            partition_completed(&sorted)
+        } else {
+            sorted
+        };
+        fuzzy_filter(&apply_pins(&sorted), &search_query)
+    };
+
+    let total_pages = page_count(visible_todos.len(), PAGE_SIZE);
+    let current_page = (*page).min(total_pages - 1);
+
+    let page_todos: Vec<Todo> = paginate(&visible_todos, current_page, PAGE_SIZE).to_vec();
+
+    let row_order = rendered_row_ids(
+        &visible_todos,
+        &page_todos,
+        RowLayout {
+            due_grouped: *due_grouped,
+            grouped: *grouped,
+            expanded_group: expanded_group.as_deref(),
+        },
+        now,
+        local_utc_offset_ms(now),
+    );
+
+    let on_move_up = {
+        let dispatch_todo = dispatch_todo.clone();
+        let flip_before = flip_before.clone();
+        let visible = row_order.clone();
+        Callback::from(move |id: String| {
+            capture_row_positions(&flip_before);
+            let visible = visible.clone();
+            dispatch_todo.emit(TodoAction::MoveUp { id, visible });
+        })
+    };
+
+    let on_move_down = {
+        let dispatch_todo = dispatch_todo.clone();
+        let flip_before = flip_before.clone();
+        let visible = row_order.clone();
+        Callback::from(move |id: String| {
+            capture_row_positions(&flip_before);
+            let visible = visible.clone();
+            dispatch_todo.emit(TodoAction::MoveDown { id, visible });
+        })
+    };
+
+    {
+        let focused_index = focused_index.clone();
+        use_effect_with(
+            (*grouped, *due_grouped, (*expanded_group).clone(), current_page),
+            move |_| focused_index.set(None),
+        );
+    }
+
+    let focused_id = focused_index.and_then(|index| row_order.get(index)).cloned();
+
+    let on_list_keydown = {
+        let focused_index = focused_index.clone();
+        let on_toggle = on_toggle.clone();
+        let on_edit = on_edit.clone();
+        let page_ids = row_order.clone();
+        Callback::from(move |e: KeyboardEvent| {
+            let target_tag = e
+                .target()
+                .and_then(|target| target.dyn_into::<web_sys::Element>().ok())
+                .map(|element| element.tag_name())
+                .unwrap_or_default();
+            if is_text_entry_tag(&target_tag) || target_tag.eq_ignore_ascii_case("BUTTON") {
+                return;
+            }
+            let focused_id = focused_index.and_then(|index| page_ids.get(index)).cloned();
+            match e.key().as_str() {
+                "ArrowDown" => focused_index.set(move_focus(*focused_index, 1, page_ids.len())),
+                "ArrowUp" => focused_index.set(move_focus(*focused_index, -1, page_ids.len())),
+                " " => {
+                    if let Some(id) = focused_id {
+                        on_toggle.emit(id);
+                    }
+                }
+                "Enter" => {
+                    if let Some(id) = focused_id {
+                        on_edit.emit(id);
+                    }
+                }
+                _ => return,
+            }
+            e.prevent_default();
+        })
+    };
+
+    let per_day = completions_per_day(&todos, now);
+    let utc_offset_ms = local_utc_offset_ms(now);
+    let projections: HashMap<String, &'static str> = todos
+        .iter()
+        .filter(|todo| !todo.completed && !todo.archived)
+        .enumerate()
+        .map(|(index, todo)| {
+            let projection = projected_completion(index, per_day, now)
+                .map_or("unknown", |at| weekday_label(at, utc_offset_ms));
+            (todo.id.clone(), projection)
+        })
+        .collect();
+    let rows = TodoRowContext {
+        now,
+        utc_offset_ms,
+        categories: settings.categories.clone(),
+        manual_sort: *sort_mode == SortMode::Manual,
+        edit_id: (*edit_id).clone(),
+        selected: (*selected).clone(),
+        focused_id,
+        expanded: (*expanded).clone(),
+        dragging_id: (*dragging_id).clone(),
+        due_popover: (*due_popover).clone(),
+        subtask_error: (*subtask_error).clone(),
+        projections,
+        edit: EditState {
+            input_ref: edit_input_ref.clone(),
+            estimate_ref: edit_estimate_ref.clone(),
+            actual_ref: edit_actual_ref.clone(),
+            title_len: *edit_title_len,
+            error: (*edit_error).clone(),
+        },
+        callbacks: TodoItemCallbacks {
+            on_toggle: on_toggle.clone(),
+            on_select: on_select.clone(),
+            on_toggle_expanded: on_toggle_expanded.clone(),
+            on_star: on_star.clone(),
+            on_pin: on_pin.clone(),
+            on_edit: on_edit.clone(),
+            on_update: on_update.clone(),
+            on_cancel: on_cancel.clone(),
+            on_edit_title_input: on_edit_title_input.clone(),
+            on_cycle_priority: on_cycle_priority.clone(),
+            on_points_change: on_points_change.clone(),
+            on_recurrence_change: on_recurrence_change.clone(),
+            on_category_change: on_category_change.clone(),
+            on_blocked_reason_change: on_blocked_reason_change.clone(),
+            on_open_due_popover: on_open_due_popover.clone(),
+            on_due_action: on_due_action.clone(),
+            on_snooze: on_snooze.clone(),
+            on_move_up: on_move_up.clone(),
+            on_move_down: on_move_down.clone(),
+            on_toggle_review: on_toggle_review.clone(),
+            on_duplicate: on_duplicate.clone(),
+            on_archive: on_archive.clone(),
+            on_add_subtask: on_add_subtask.clone(),
+            on_toggle_subtask: on_toggle_subtask.clone(),
+            on_delete_subtask: on_delete_subtask.clone(),
+            on_drag_start: on_drag_start.clone(),
+            on_drop: on_drop.clone(),
+        },
+    };
+
+    let render_archived_todo = |id: String, title: String| {
+        let id_for_restore = id.clone();
+        let id_for_delete = id;
+        html! {
+            <li role="listitem" class="flex items-center p-2 border rounded text-gray-500">
+                <span class="flex-grow">{ title.clone() }</span>
+                <button
+                    onclick={on_restore.reform(move |_| id_for_restore.clone())}
+                    aria-label={action_aria_label("Restore", &title)}
+                    class={format!("{} {}", BUTTON_CLASS, RESTORE_BUTTON)}
+                >
+                    {"Restore"}
+                </button>
+                <button
+                    onclick={on_delete.reform(move |_| id_for_delete.clone())}
+                    aria-label={action_aria_label("Delete", &title)}
+                    class={format!("{} {}", BUTTON_CLASS, DELETE_BUTTON)}
+                >
+                    {"Delete"}
+                </button>
+            </li>
+        }
+    };
+
+    let archived_count = todos.iter().filter(|todo| todo.archived).count();
+    let planned_points = active_points(&todos);
+    let (estimated_minutes, actual_minutes) = sum_estimates(&todos);
+    let is_over_capacity = settings
+        .capacity
+        .is_some_and(|capacity| over_capacity(&todos, capacity));
+
+    let counts = filter_counts(&todos);
+    let completed_count = filter_count(Filter::Completed, counts);
+    let rendered_todos = if *grouped || *due_grouped { &visible_todos } else { &page_todos };
+    let rendered_ids: HashSet<String> = rendered_todos.iter().map(|todo| todo.id.clone()).collect();
+    let has_visible_active = rendered_todos.iter().any(|todo| !todo.completed);
+    let select_all = select_all_state(&selected, &rendered_ids);
+
+    {
+        let select_all_ref = select_all_ref.clone();
+        use_effect_with(select_all, move |select_all| {
+            if let Some(input) = select_all_ref.cast::<HtmlInputElement>() {
+                input.set_indeterminate(select_all.is_none());
+            }
+        });
+    }
+
+    if *printing {
+        return render_print_view(&visible_todos, on_close_print);
+    }
+
+    html! {
+        <div class="container mx-auto p-4 max-w-md">
+            <h1 class="text-2xl font-bold mb-4 text-center">{"Todo App"}</h1>
+            <div class="flex items-center gap-2 mb-2 text-sm">
+                <label for="profile-select" class="text-gray-600">{"Profile"}</label>
+                <select
+                    id="profile-select"
+                    onchange={on_profile_change.reform(|e: Event| e.target_unchecked_into::<HtmlSelectElement>().value())}
+                    class="p-1 border rounded"
+                >
+                    { for list_profiles(&profile).into_iter().map(|name| html! {
+                        <option value={name.clone()} selected={*profile == name}>{ name }</option>
+                    })}
+                </select>
+                <input
+                    type="text"
+                    placeholder="New profile"
+                    onkeydown={Callback::from({
+                        let on_profile_change = on_profile_change.clone();
+                        move |e: KeyboardEvent| {
+                            if e.key() == "Enter" {
+                                e.prevent_default();
+                                let input = e.target_unchecked_into::<HtmlInputElement>();
+                                on_profile_change.emit(input.value());
+                                clear_input(&input);
+                            }
+                        }
+                    })}
+                    class="flex-grow p-1 border rounded"
+                />
+                <select
+                    aria-label="Storage"
+                    onchange={on_storage_mode_change.reform(|e: Event| e.target_unchecked_into::<HtmlSelectElement>().value())}
+                    class="p-1 border rounded"
+                >
+                    { for StorageMode::ALL.into_iter().map(|mode| html! {
+                        <option value={mode.value()} selected={*storage_mode == mode}>{ mode.label() }</option>
+                    })}
+                </select>
+            </div>
+            <TodoForm
+                input_ref={input_ref}
+                due_ref={new_due_ref}
+                title_len={*new_title_len}
+                allow_placeholder={settings.allow_placeholder}
+                auto_capitalize={settings.auto_capitalize}
+                on_submit={on_submit}
+                on_title_input={on_new_title_input}
+                on_toggle_placeholder={on_toggle_placeholder}
+                on_toggle_auto_capitalize={on_toggle_auto_capitalize}
+            />
+            <details class="mb-4 text-sm">
+                <summary class="cursor-pointer text-gray-600">{"Categories"}</summary>
+                <div class="flex flex-wrap gap-1 mt-2">
+                    { for settings.categories.iter().map(|category| html! {
+                        <span
+                            style={format!("border-left: 4px solid {}", category_border_color(Some(category)))}
+                            class="px-2 py-1 border rounded"
+                        >
+                            { category.name.clone() }
+                        </span>
+                    })}
+                </div>
+                <form onsubmit={on_add_category} class="flex gap-2 mt-2">
+                    <input
+                        type="text"
+                        ref={category_name_ref}
+                        placeholder="New category"
+                        class="flex-grow p-1 border rounded"
+                    />
+                    <input type="color" ref={category_color_ref} value={DEFAULT_CATEGORY_COLOR} />
+                    <button type="submit" class="px-2 py-1 rounded border">{"Add"}</button>
+                </form>
+            </details>
+            <div aria-live="polite" role="status">
+                if fallback_active() {
+                    <div class="mb-2 p-2 text-sm text-amber-800 bg-amber-100 rounded">
+                        {"Changes won't be saved"}
+                    </div>
+                }
+                if save_conflict() {
+                    <p class="flex items-center gap-2 mb-2 p-2 text-sm text-amber-800 bg-amber-100 rounded">
+                        <span class="flex-grow">{ CONFLICT_MESSAGE }</span>
+                        <button onclick={on_reload_conflict} class="px-2 border border-amber-800 rounded">
+                            {"Reload"}
+                        </button>
+                        <button onclick={on_overwrite_conflict} class="px-2 border border-amber-800 rounded">
+                            {"Keep mine"}
+                        </button>
+                    </p>
+                } else if let Some(error) = (*storage_error).clone() {
+                    <ErrorBanner
+                        message={error}
+                        on_retry={PENDING_WRITE.with(|pending| pending.borrow().is_pending()).then_some(on_retry_save)}
+                        on_dismiss={on_dismiss_error}
+                    />
+                }
+            </div>
+            <input
+                type="search"
+                placeholder="Search"
+                aria-label="Search todos"
+                value={(*search_query).clone()}
+                oninput={on_search_input}
+                class="w-full mb-2 p-1 border rounded text-sm"
+            />
+            <div class="flex items-center gap-2 mb-2">
+                { for Filter::ALL.into_iter().map(|option| {
+                    let selectable = is_filter_selectable(option, counts);
+                    html! {
+                        <button
+                            onclick={on_filter.reform(move |_| option)}
+                            disabled={!selectable}
+                            class={classes!(
+                                "px-2", "py-1", "text-sm", "rounded", "border",
+                                (filter == option).then_some("bg-blue-500 text-white"),
+                                (!selectable).then_some("opacity-50 cursor-not-allowed")
+                            )}
+                        >
+                            { format!("{} ({})", option.label(), filter_count(option, counts)) }
+                        </button>
+                    }
+                })}
+                <input
+                    type="checkbox"
+                    ref={select_all_ref}
+                    title="Select all visible"
+                    aria-label="Select all visible"
+                    checked={select_all == Some(true)}
+                    disabled={rendered_ids.is_empty()}
+                    onchange={on_select_all.reform({
+                        let rendered_ids = rendered_ids.clone();
+                        move |e: Event| {
+                            let input = e.target_unchecked_into::<HtmlInputElement>();
+                            (input.checked(), rendered_ids.clone())
+                        }
+                    })}
+                    class="accent-gray-500"
+                />
+                <button
+                    onclick={on_complete_visible.reform(move |_| rendered_ids.clone())}
+                    disabled={!has_visible_active}
+                    class="px-2 py-1 text-sm rounded border disabled:opacity-50"
+                >
+                    {"Complete visible"}
+                </button>
+                <span aria-live="polite" class="ml-auto text-sm text-gray-500">
+                    { remaining_label(count_active(&todos)) }
+                </span>
+            </div>
+            <div class="flex items-center gap-2 mb-2 text-sm">
+                <span>
+                    { match settings.capacity {
+                        Some(capacity) => format!("{} / {} points", planned_points, capacity),
+                        None => format!("{} points", planned_points),
+                    } }
+                </span>
+                <span class="text-gray-600">
+                    { format!("Estimated {} min / Actual {} min", estimated_minutes, actual_minutes) }
+                </span>
+                <label class="flex items-center gap-1 text-gray-600">
+                    {"Capacity"}
+                    <input
+                        type="number"
+                        min="0"
+                        value={settings.capacity.map(|capacity| capacity.to_string()).unwrap_or_default()}
+                        onchange={on_capacity_change}
+                        class="w-16 p-1 border rounded"
+                    />
+                </label>
+            </div>
+            { render_completion_chart(&completions_by_day(&todos, now, utc_offset_ms), now, utc_offset_ms) }
+            if is_over_capacity {
+                <p class="mb-2 p-2 text-sm text-orange-800 bg-orange-100 rounded">
+                    {"Active points exceed your capacity."}
+                </p>
+            }
+            <div class="flex justify-end gap-2 mb-2">
+                <button
+                    onclick={on_toggle_recent}
+                    class={classes!("px-2", "py-1", "text-sm", "rounded", "border", recent_only.then_some("bg-blue-100"))}
+                >
+                    {"Finished in last hour"}
+                </button>
+                <button
+                    onclick={on_toggle_today}
+                    class={classes!("px-2", "py-1", "text-sm", "rounded", "border", today_only.then_some("bg-blue-100"))}
+                >
+                    {"Completed today"}
+                </button>
+                <button
+                    onclick={on_toggle_blocked_only}
+                    class={classes!("px-2", "py-1", "text-sm", "rounded", "border", blocked_only.then_some("bg-blue-100"))}
+                >
+                    {"Blocked"}
+                </button>
+                <button
+                    onclick={on_toggle_grouped}
+                    class={classes!("px-2", "py-1", "text-sm", "rounded", "border", grouped.then_some("bg-blue-100"))}
+                >
+                    {"Group by tag"}
+                </button>
+                <button
+                    onclick={on_toggle_due_grouped}
+                    class={classes!("px-2", "py-1", "text-sm", "rounded", "border", due_grouped.then_some("bg-blue-100"))}
+                >
+                    {"Group by due"}
+                </button>
+                if !contexts(&todos).is_empty() {
+                    <select onchange={on_context_filter_change} class="p-1 border rounded text-sm">
+                        <option value="" selected={context_filter.is_none()}>{"All contexts"}</option>
+                        { for contexts(&todos).into_iter().map(|context| html! {
+                            <option value={context.clone()} selected={context_filter.as_deref() == Some(context.as_str())}>
+                                { format!("@{}", context) }
+                            </option>
+                        })}
+                    </select>
+                }
+                <button
+                    onclick={on_toggle_completed_last}
+                    class={classes!("px-2", "py-1", "text-sm", "rounded", "border", completed_last.then_some("bg-blue-100"))}
+                >
+                    {"Completed last"}
+                </button>
+                <button
+                    onclick={on_start_weekly_review}
+                    class="px-2 py-1 text-sm rounded border"
+                >
+                    {"Weekly review"}
+                </button>
+                if notifications_supported() && !*reminders_enabled {
+                    <button
+                        onclick={on_enable_reminders}
+                        class="px-2 py-1 text-sm rounded border"
+                    >
+                        {"Enable reminders"}
+                    </button>
+                }
+                <select onchange={on_sort_mode_change} class="p-1 border rounded text-sm">
+                    { for SortMode::ALL.into_iter().map(|mode| html! {
+                        <option value={mode.value()} selected={*sort_mode == mode}>
+                            { mode.label() }
+                        </option>
+                    })}
+                </select>
+                <label class="flex items-center gap-1 text-sm text-gray-600">
+                    <input
+                        type="checkbox"
+                        checked={settings.sort_on_load}
+                        onclick={on_toggle_sort_on_load}
+                    />
+                    {"Apply sort on load"}
+                </label>
+            </div>
+            if !selected.is_empty() {
+                <div class="flex items-center gap-2 mb-2 p-2 bg-gray-100 rounded">
+                    <span class="flex-grow text-sm">{ format!("{} selected", selected.len()) }</span>
+                    <button
+                        onclick={on_move_selected_to_edge.reform(|_| true)}
+                        class={format!("{} {}", BUTTON_CLASS, CANCEL_BUTTON)}
+                    >
+                        {"Move to top"}
+                    </button>
+                    <button
+                        onclick={on_move_selected_to_edge.reform(|_| false)}
+                        class={format!("{} {}", BUTTON_CLASS, CANCEL_BUTTON)}
+                    >
+                        {"Move to bottom"}
+                    </button>
+                    <button
+                        onclick={on_clear_tags_selected}
+                        class={format!("{} {}", BUTTON_CLASS, CANCEL_BUTTON)}
+                    >
+                        {"Clear tags"}
+                    </button>
+                    <button
+                        onclick={on_delete_selected}
+                        class={format!("{} {}", BUTTON_CLASS, DELETE_BUTTON)}
+                    >
+                        {"Delete selected"}
+                    </button>
+                </div>
+            }
+            if *due_grouped {
+                { for group_by_due(&visible_todos, now, utc_offset_ms).sections().into_iter().filter(|(_, group)| !group.is_empty()).map(|(label, group)| html! {
+                    <section class="mb-2">
+                        <h2 class="p-1 text-sm font-semibold bg-gray-100 rounded">
+                            { format!("{} ({})", label, group.len()) }
+                        </h2>
+                        <TodoList
+                            todos={group.to_vec()}
+                            label={label}
+                            class="mt-1 space-y-2 focus:outline-none"
+                            rows={rows.clone()}
+                            onkeydown={on_list_keydown.clone()}
+                        />
+                    </section>
+                })}
+            } else if *grouped {
+                { for group_by_tag(&visible_todos).into_iter().map(|(key, group)| {
+                    let is_expanded = expanded_group.as_deref() == Some(key.as_str());
+                    html! {
+                        <section class="mb-2">
+                            <button
+                                onclick={on_toggle_group.reform({
+                                    let key = key.clone();
+                                    move |_| key.clone()
+                                })}
+                                class="w-full p-1 text-left text-sm font-semibold bg-gray-100 rounded"
+                            >
+                                { format!("{} {} ({})", if is_expanded { "▾" } else { "▸" }, key, group.len()) }
+                            </button>
+                            if is_expanded {
+                                <TodoList
+                                    todos={group.clone()}
+                                    label={key.clone()}
+                                    class="mt-1 space-y-2 focus:outline-none"
+                                    rows={rows.clone()}
+                                    onkeydown={on_list_keydown.clone()}
+                                />
+                            }
+                        </section>
+                    }
+                })}
+            } else {
+                <TodoList
+                    todos={page_todos}
+                    label="Todos"
+                    class="max-h-[70vh] overflow-y-auto space-y-2 focus:outline-none"
+                    rows={rows}
+                    list_ref={list_ref}
+                    onscroll={on_list_scroll}
+                    onkeydown={on_list_keydown}
+                />
+                if total_pages > 1 {
+                    <div class="flex items-center justify-center gap-2 mt-2 text-sm">
+                        <button
+                            onclick={on_page.reform(move |_| current_page.saturating_sub(1))}
+                            disabled={current_page == 0}
+                            class="px-2 py-1 border rounded disabled:opacity-50"
+                        >
+                            {"Prev"}
+                        </button>
+                        <span>{ format!("page {} of {}", current_page + 1, total_pages) }</span>
+                        <button
+                            onclick={on_page.reform(move |_| current_page + 1)}
+                            disabled={current_page + 1 >= total_pages}
+                            class="px-2 py-1 border rounded disabled:opacity-50"
+                        >
+                            {"Next"}
+                        </button>
+                    </div>
+                }
+            }
+            <div class="flex gap-2 mt-4">
+                <button
+                    onclick={on_history_undo.reform(|_| ())}
+                    disabled={todos.past.is_empty()}
+                    class={format!("{} {} disabled:opacity-50", BUTTON_CLASS, CANCEL_BUTTON)}
+                >
+                    {"Undo"}
+                </button>
+                <button
+                    onclick={on_history_redo.reform(|_| ())}
+                    disabled={todos.future.is_empty()}
+                    class={format!("{} {} disabled:opacity-50", BUTTON_CLASS, CANCEL_BUTTON)}
+                >
+                    {"Redo"}
+                </button>
+            </div>
+            <div class="flex items-center gap-2 mt-4">
+                <select onchange={on_export_format_change} class="p-1 border rounded">
+                    { for ExportFormat::ALL.into_iter().map(|format| html! {
+                        <option value={format.value()} selected={*export_format == format}>
+                            { format.label() }
+                        </option>
+                    })}
+                </select>
+                <button onclick={on_export_download} class={format!("{} {}", BUTTON_CLASS, ADD_BUTTON)}>
+                    {"Download"}
+                </button>
+                <button onclick={on_export_copy} class={format!("{} {}", BUTTON_CLASS, ADD_BUTTON)}>
+                    {"Copy"}
+                </button>
+                <button onclick={on_export_csv} class={format!("{} {}", BUTTON_CLASS, ADD_BUTTON)}>
+                    {"Export CSV"}
+                </button>
+                <button onclick={on_copy_list} class={format!("{} {}", BUTTON_CLASS, ADD_BUTTON)}>
+                    {"Copy list"}
+                </button>
+                <button onclick={on_print} class={format!("{} {}", BUTTON_CLASS, ADD_BUTTON)}>
+                    {"Print"}
+                </button>
+                <button onclick={on_export_tag_report} class={format!("{} {}", BUTTON_CLASS, ADD_BUTTON)}>
+                    {"Tag report (CSV)"}
+                </button>
+            </div>
+            <div class="flex items-center gap-2 mt-4">
+                <label class="text-sm text-gray-600">
+                    {"Import JSON/CSV "}
+                    <input type="file" accept=".json,.csv,application/json,text/csv" onchange={on_import} class="text-sm" />
+                </label>
+                <button
+                    onclick={on_revert}
+                    disabled={!differs(&load_snapshot.borrow(), &todos)}
+                    class={format!("{} {} disabled:opacity-50", BUTTON_CLASS, CANCEL_BUTTON)}
+                >
+                    {"Revert to last snapshot"}
+                </button>
+            </div>
+            <div class="flex items-center gap-2 mt-4">
+                if *confirm_clear_all {
+                    <span class="text-sm text-red-600">{ format!("Delete all {} todos?", todos.len()) }</span>
+                    <button onclick={on_clear_all} class={format!("{} {}", BUTTON_CLASS, DELETE_BUTTON)}>
+                        {"Yes, clear all"}
+                    </button>
+                    <button onclick={on_cancel_clear_all} class={format!("{} {}", BUTTON_CLASS, CANCEL_BUTTON)}>
+                        {"Cancel"}
+                    </button>
+                } else {
+                    <button
+                        onclick={on_clear_all}
+                        disabled={todos.is_empty()}
+                        class={format!("{} {} disabled:opacity-50", BUTTON_CLASS, DELETE_BUTTON)}
+                    >
+                        {"Clear all"}
+                    </button>
+                }
+                if *confirm_clear_completed {
+                    <span class="text-sm text-red-600">{ format!("Clear {} completed?", completed_count) }</span>
+                    <button onclick={on_clear_completed} class={format!("{} {}", BUTTON_CLASS, DELETE_BUTTON)}>
+                        {"Yes, clear"}
+                    </button>
+                    <button onclick={on_cancel_clear_completed} class={format!("{} {}", BUTTON_CLASS, CANCEL_BUTTON)}>
+                        {"Cancel"}
+                    </button>
+                } else {
+                    <button
+                        onclick={on_clear_completed}
+                        disabled={completed_count == 0}
+                        class={format!("{} {} disabled:opacity-50", BUTTON_CLASS, DELETE_BUTTON)}
+                    >
+                        {"Clear completed"}
+                    </button>
+                }
+                <button
+                    onclick={on_remove_duplicates}
+                    class={format!("{} {}", BUTTON_CLASS, CANCEL_BUTTON)}
+                >
+                    {"Remove duplicates"}
+                </button>
+            </div>
+            <button onclick={on_toggle_archived} class="mt-4 text-sm text-gray-600 hover:underline">
+                { if *show_archived {
+                    "Hide archived".to_string()
+                } else {
+                    format!("Show archived ({})", archived_count)
+                } }
+            </button>
+            if *show_archived {
+                <ul role="list" aria-label="Archived todos" class="mt-2 space-y-2">
+                    { for (*todos).iter().filter(|todo| todo.archived).map(|todo| {
+                        render_archived_todo(todo.id.clone(), todo.title.clone())
+                    })}
+                </ul>
+            }
+            if *show_diagnostics {
+                <section class="mt-4 p-2 text-xs font-mono bg-gray-100 border rounded">
+                    <h2 class="mb-1 font-semibold">{"Diagnostics"}</h2>
+                    {{
+                        let diagnostics = collect_diagnostics(&todos);
+                        html! {
+                            <ul>
+                                <li>{ format!("storage available: {}", storage_available()) }</li>
+                                <li>{ format!("items: {} ({} archived)", diagnostics.item_count, diagnostics.archived_count) }</li>
+                                <li>{ format!("serialized size: {} bytes", diagnostics.serialized_bytes) }</li>
+                                <li>{ format!("schema version: {}", diagnostics.schema_version) }</li>
+                                <li>{ format!("app version: {}", diagnostics.app_version) }</li>
+                                <li>{ format!("pending write: {}", PENDING_WRITE.with(|pending| pending.borrow().is_pending())) }</li>
+                                <li>{ format!("last error: {}", storage_error.as_deref().unwrap_or("none")) }</li>
+                            </ul>
+                        }
+                    }}
+                </section>
+            }
+            if let Some((queue, index)) = weekly_review.as_ref() {
+                <div class="fixed inset-0 z-20 flex items-center justify-center bg-black/40">
+                    <div class="w-80 p-4 bg-white rounded shadow">
+                        <div class="flex justify-between mb-2 text-sm text-gray-500">
+                            <span>{"Weekly review"}</span>
+                            <span>{ format!("{} of {}", index + 1, queue.len()) }</span>
+                        </div>
+                        <p class="mb-4 font-semibold">
+                            { todos
+                                .iter()
+                                .find(|todo| Some(&todo.id) == queue.get(*index))
+                                .map_or_else(|| "(removed)".to_string(), |todo| todo.title.clone()) }
+                        </p>
+                        <div class="flex flex-wrap justify-end gap-2">
+                            { for [
+                                ("Keep", ReviewStep::Keep, CANCEL_BUTTON),
+                                ("Complete", ReviewStep::Complete, SAVE_BUTTON),
+                                ("Snooze", ReviewStep::Snooze, SNOOZE_BUTTON),
+                                ("Delete", ReviewStep::Delete, DELETE_BUTTON),
+                            ]
+                            .into_iter()
+                            .map(|(label, step, class)| html! {
+                                <button
+                                    onclick={on_weekly_review_step.reform(move |_| step)}
+                                    class={format!("{} {}", BUTTON_CLASS, class)}
+                                >
+                                    { label }
+                                </button>
+                            })}
+                            <button onclick={on_close_weekly_review} class="px-2 text-sm text-gray-500 hover:underline">
+                                {"Close"}
+                            </button>
+                        </div>
+                    </div>
+                </div>
+            }
+            if let Some(todo) = review_id.as_ref().and_then(|id| todos.iter().find(|todo| &todo.id == id)) {
+                <div class="fixed inset-0 z-20 flex items-center justify-center bg-black/40">
+                    <div class="w-80 p-4 bg-white rounded shadow">
+                        <h2 class="mb-2 font-semibold">{ format!("Review \"{}\"", todo.title) }</h2>
+                        if todo.subtasks.is_empty() {
+                            <p class="mb-2 text-sm text-gray-500">{"No checklist items."}</p>
+                        } else {
+                            <ul class="mb-2 text-sm">
+                                { for todo.subtasks.iter().map(|subtask| html! {
+                                    <li class={classes!(subtask.done.then_some("line-through"))}>
+                                        { format!("{} {}", if subtask.done { "☑" } else { "☐" }, subtask.title) }
+                                    </li>
+                                })}
+                            </ul>
+                        }
+                        <div class="flex justify-end gap-2">
+                            <button onclick={on_cancel_review} class={format!("{} {}", BUTTON_CLASS, CANCEL_BUTTON)}>
+                                {"Cancel"}
+                            </button>
+                            <button onclick={on_confirm_review} class={format!("{} {}", BUTTON_CLASS, SAVE_BUTTON)}>
+                                {"Confirm and complete"}
+                            </button>
+                        </div>
+                    </div>
+                </div>
+            }
+            if let Some((message, _)) = &*last_snapshot {
+                <div class="fixed bottom-4 left-1/2 -translate-x-1/2 flex items-center gap-4 px-4 py-2 rounded bg-gray-800 text-white shadow">
+                    <span>{*message}</span>
+                    <button onclick={on_undo} class="font-semibold text-yellow-300 hover:underline">
+                        {"Undo"}
+                    </button>
+                </div>
+            }
+        </div>
+    }
+}
+
+#[function_component(Root)]
+fn root() -> Html {
+    html! {
+        <BrowserRouter>
+            <App />
+        </BrowserRouter>
+    }
+}
+
+fn main() {
+    yew::Renderer::<Root>::new().render();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::components::collect_text;
+
+    #[test]
+    fn should_add_new_todo_to_existing_list() {
+        let todos = vec![Todo {
+            id: "1".to_string(),
+            title: "Create Yew + TW + Rust App".to_string(),
+            completed: false,
+            ..Default::default()
+        }];
+        let new_todos = create_new_todo(&todos, "New Task".to_string(), false, 0.0);
+        assert_eq!(new_todos.len(), 2);
+        assert_eq!(new_todos[1].title, "New Task");
+        assert!(!new_todos[1].completed);
+    }
+
+    #[test]
+    fn should_reduce_add_delete_and_toggle_actions() {
+        let added = reduce(
+            &sample_todos(2),
+            TodoAction::Add {
+                title: "Task 3".to_string(),
+                auto_capitalize: false,
+                due_at: Some(7.0),
+                now: 5.0,
+            },
+        )
+        .unwrap();
+        assert_eq!(added.len(), 3);
+        assert_eq!(added[2].title, "Task 3");
+        assert_eq!(added[2].due_at, Some(7.0));
+        assert_eq!(added[2].created_at, Some(5.0));
+        let toggled = reduce(
+            &added,
+            TodoAction::Toggle {
+                id: "1".to_string(),
+                now: 9.0,
+                utc_offset_ms: 0.0,
+            },
+        )
+        .unwrap();
+        assert!(toggled[0].completed);
+        assert_eq!(toggled[0].completed_at, Some(9.0));
+        let deleted = reduce(&toggled, TodoAction::Delete { id: "1".to_string() }).unwrap();
+        assert_eq!(deleted.len(), 2);
+        assert_eq!(deleted[0].id, "2");
+    }
+
+    #[test]
+    fn should_reduce_title_and_flag_actions() {
+        let todos = sample_todos(2);
+        let renamed = reduce(
+            &todos,
+            TodoAction::UpdateTitle {
+                id: "2".to_string(),
+                title: " Renamed ".to_string(),
+                now: 1.0,
+            },
+        )
+        .unwrap();
+        assert_eq!(renamed[1].title, "Renamed");
+        assert!(reduce(&todos, TodoAction::Star { id: "1".to_string() }).unwrap()[0].starred);
+        assert!(reduce(&todos, TodoAction::Pin { id: "1".to_string() }).unwrap()[0].pinned);
+        assert!(reduce(&todos, TodoAction::Archive { id: "2".to_string() }).unwrap()[1].archived);
+        let moved = reduce(
+            &todos,
+            TodoAction::MoveDown {
+                id: "1".to_string(),
+                visible: vec!["1".to_string(), "2".to_string()],
+            },
+        )
+        .unwrap();
+        assert_eq!(ids(&moved), vec!["2", "1"]);
+        assert_eq!(
+            reduce(
+                &moved,
+                TodoAction::MoveUp {
+                    id: "1".to_string(),
+                    visible: vec!["2".to_string(), "1".to_string()],
+                },
+            ),
+            Ok(todos)
+        );
+    }
+
+    fn command(action: TodoAction) -> TodoCommand {
+        TodoCommand {
+            action,
+            now: 1.0,
+            outcome: CommandOutcome::default(),
+        }
+    }
+
+    #[test]
+    fn should_apply_consecutive_actions_to_latest_state() {
+        let state = std::rc::Rc::new(UndoableState::new(sample_todos(2)))
+            .reduce(HistoryAction::Apply(command(TodoAction::Star { id: "1".to_string() })))
+            .reduce(HistoryAction::Apply(command(TodoAction::Pin { id: "2".to_string() })));
+        assert!(state[0].starred && state[1].pinned);
+        assert_eq!(state[0].updated_at, Some(1.0));
+        assert_eq!(state.past.len(), 2);
+    }
+
+    #[test]
+    fn should_report_rejected_action_through_outcome() {
+        let state = std::rc::Rc::new(UndoableState::new(sample_todos(1)));
+        let rejected = command(TodoAction::AddSubtask {
+            id: "1".to_string(),
+            title: " ".to_string(),
+        });
+        let outcome = rejected.outcome.clone();
+        let next = state.clone().reduce(HistoryAction::Apply(rejected));
+        assert_eq!(next, state);
+        assert_eq!(outcome.take(), Some(Err("Subtask can't be empty".to_string())));
+    }
+
+    #[test]
+    fn should_reduce_bulk_and_subtask_actions() {
+        let todos = sample_todos(3);
+        let selected = HashSet::from(["1".to_string(), "3".to_string()]);
+        let deleted = reduce(&todos, TodoAction::DeleteMany { ids: selected.clone() }).unwrap();
+        assert_eq!(ids(&deleted), vec!["2"]);
+        let moved = reduce(&todos, TodoAction::MoveToEdge { ids: selected, to_top: false }).unwrap();
+        assert_eq!(ids(&moved), vec!["2", "1", "3"]);
+        let added = reduce(
+            &todos,
+            TodoAction::AddSubtask {
+                id: "1".to_string(),
+                title: "Step".to_string(),
+            },
+        )
+        .unwrap();
+        assert_eq!(added[0].subtasks[0].title, "Step");
+        assert_eq!(
+            reduce(
+                &todos,
+                TodoAction::AddSubtask {
+                    id: "1".to_string(),
+                    title: " ".to_string(),
+                },
+            ),
+            Err("Subtask can't be empty".to_string())
+        );
+        assert_eq!(reduce(&todos, TodoAction::ClearAll), Ok(Vec::new()));
+        assert_eq!(reduce(&todos, TodoAction::Replace { todos: sample_todos(1) }), Ok(sample_todos(1)));
+    }
+
+    #[test]
+    fn should_apply_batched_actions_in_order() {
+        let todos = sample_todos(2);
+        let edited = reduce(
+            &todos,
+            TodoAction::Batch(vec![
+                TodoAction::SetMinutes {
+                    id: "2".to_string(),
+                    estimate_minutes: Some(30),
+                    actual_minutes: None,
+                },
+                TodoAction::UpdateTitle {
+                    id: "2".to_string(),
+                    title: "Renamed".to_string(),
+                    now: 1.0,
+                },
+            ]),
+        )
+        .unwrap();
+        assert_eq!(edited[1].title, "Renamed");
+        assert_eq!(edited[1].estimate_minutes, Some(30));
+    }
+
+    #[test]
+    fn should_reject_add_action_at_limit() {
+        let action = TodoAction::Add {
+            title: "One too many".to_string(),
+            auto_capitalize: false,
+            due_at: None,
+            now: 0.0,
+        };
+        assert_eq!(
+            reduce(&sample_todos(MAX_TODOS), action),
+            Err("Limit reached (500 tasks)".to_string())
+        );
+    }
+
+    #[test]
+    fn should_add_todo_just_below_limit() {
+        let todos = sample_todos(MAX_TODOS - 1);
+        let new_todos = try_create_new_todo(&todos, "Last one".to_string(), false, 0.0).unwrap();
+        assert_eq!(new_todos.len(), MAX_TODOS);
+        assert_eq!(new_todos[MAX_TODOS - 1].title, "Last one");
+    }
+
+    #[test]
+    fn should_refuse_to_add_when_list_is_at_limit() {
+        let todos = sample_todos(MAX_TODOS);
+        assert_eq!(
+            try_create_new_todo(&todos, "One too many".to_string(), false, 0.0),
+            Err("Limit reached (500 tasks)".to_string())
+        );
+    }
+
+    #[test]
+    fn should_complete_only_visible_todos() {
+        let todos = sample_todos(4);
+        let visible: HashSet<String> = ["2", "3"].map(str::to_string).into();
+        let new_todos = toggle_visible(&todos, &visible, true, 7.0);
+        assert_eq!(
+            new_todos.iter().map(|todo| todo.completed).collect::<Vec<_>>(),
+            vec![false, true, true, false]
+        );
+        assert_eq!(new_todos[1].completed_at, Some(7.0));
+        assert_eq!(new_todos[0], todos[0]);
+        assert_eq!(new_todos[3], todos[3]);
+    }
+
+    #[test]
+    fn should_reopen_visible_todos_and_skip_unreviewed_ones() {
+        let mut todos = sample_todos(3);
+        todos[0].completed = true;
+        todos[0].completed_at = Some(1.0);
+        todos[1].require_review = true;
+        let visible: HashSet<String> = ["1", "2"].map(str::to_string).into();
+        let completed = toggle_visible(&todos, &visible, true, 7.0);
+        assert_eq!(completed[0].completed_at, Some(1.0));
+        assert!(!completed[1].completed);
+        let reopened = toggle_visible(&todos, &visible, false, 7.0);
+        assert!(!reopened[0].completed);
+        assert_eq!(reopened[0].completed_at, None);
+        assert_eq!(reopened[2], todos[2]);
+    }
+
+    #[test]
+    fn should_title_case_multi_word_titles() {
+        assert_eq!(to_title_case("buy milk today"), "Buy Milk Today");
+        assert_eq!(to_title_case("  call   mom "), "Call Mom");
+    }
+
+    #[test]
+    fn should_keep_small_words_lowercase_unless_first() {
+        assert_eq!(to_title_case("the lord of the rings"), "The Lord of the Rings");
+        assert_eq!(to_title_case("a tale AND a song"), "A Tale and a Song");
+    }
+
+    #[test]
+    fn should_preserve_already_capitalized_input() {
+        assert_eq!(to_title_case("Review The NASA Report"), "Review the NASA Report");
+        assert_eq!(to_title_case("Ship It"), "Ship It");
+    }
+
+    #[test]
+    fn should_apply_title_case_when_creating_with_auto_capitalize() {
+        let new_todos = create_new_todo(&[], "water the plants #home".to_string(), true, 0.0);
+        assert_eq!(new_todos[0].title, "Water the Plants");
+        assert_eq!(new_todos[0].tags, vec!["home".to_string()]);
+        let unchanged = create_new_todo(&[], "water the plants".to_string(), false, 0.0);
+        assert_eq!(unchanged[0].title, "water the plants");
+    }
+
+    #[test]
+    fn should_validate_non_empty_title() {
+        assert!(is_valid_title("Welcom Rust"));
+    }
+
+    #[test]
+    fn should_invalidate_empty_or_whitespace_title() {
+        assert!(!is_valid_title(""));
+        assert!(!is_valid_title("  "));
+    }
+
+    #[test]
+    fn should_count_unicode_scalar_values_not_bytes() {
+        assert_eq!(title_length("abc"), 3);
+        assert_eq!(title_length("héllo"), 5);
+        assert_eq!(title_length("日本語"), 3);
+        assert_eq!("日本語".len(), 9);
+        assert_eq!(title_length("👍🏽"), 2);
+    }
+
+    #[test]
+    fn should_invalidate_titles_over_max_length() {
+        assert!(is_valid_title(&"a".repeat(MAX_TITLE_LEN)));
+        assert!(!is_valid_title(&"a".repeat(MAX_TITLE_LEN + 1)));
+        assert!(is_valid_title(&"é".repeat(MAX_TITLE_LEN)));
+    }
+
+    #[test]
+    fn should_remove_todo_by_id() {
+        let todos = vec![
+            Todo {
+                id: "1".to_string(),
+                title: "Task 1".to_string(),
+                completed: false,
+                ..Default::default()
+            },
+            Todo {
+                id: "2".to_string(),
+                title: "Task 2".to_string(),
+                completed: true,
+                ..Default::default()
+            },
+        ];
+        let new_todos = delete_todo(&todos, "1");
+        assert_eq!(new_todos.len(), 1);
+        assert_eq!(new_todos[0].id, "2");
+        assert_eq!(new_todos[0].title, "Task 2");
+        assert!(new_todos[0].completed);
+    }
+
+    #[test]
+    fn should_toggle_todo_completion_status() {
+        let todos = vec![
+            Todo {
+                id: "1".to_string(),
+                title: "Task 1".to_string(),
+                completed: false,
+                ..Default::default()
+            },
+            Todo {
+                id: "2".to_string(),
+                title: "Task 2".to_string(),
+                completed: true,
+                ..Default::default()
+            },
+        ];
+        let new_todos = toggle_todo(&todos, "1", 0.0);
+        assert_eq!(new_todos.len(), 2);
+        assert_eq!(new_todos[0].id, "1");
+        assert_eq!(new_todos[0].title, "Task 1");
+        assert!(new_todos[0].completed);
+        assert_eq!(new_todos[1].id, "2");
+        assert_eq!(new_todos[1].title, "Task 2");
+        assert!(new_todos[1].completed);
+    }
+
+    #[test]
+    fn should_record_old_title_in_history_on_update() {
+        let todos = sample_todos(2);
+        let new_todos = update_todo_title(&todos, "1", "Renamed", 42.0);
+        assert_eq!(new_todos[0].title, "Renamed");
+        assert_eq!(new_todos[0].title_history, vec![(42.0, "Task 1".to_string())]);
+        assert!(new_todos[1].title_history.is_empty());
+        let unchanged = update_todo_title(&new_todos, "1", "Renamed", 43.0);
+        assert_eq!(unchanged[0].title_history.len(), 1);
+    }
+
+    #[test]
+    fn should_accept_valid_edit_including_unchanged_title() {
+        let todos = sample_todos(2);
+        assert_eq!(validate_edit(&todos, "1", "Renamed"), Ok(()));
+        assert_eq!(validate_edit(&todos, "1", " Task 1 "), Ok(()));
+    }
+
+    #[test]
+    fn should_reject_empty_and_too_long_edits() {
+        let todos = sample_todos(1);
+        assert_eq!(validate_edit(&todos, "1", "   "), Err("Title can't be empty".to_string()));
+        assert_eq!(
+            validate_edit(&todos, "1", &"x".repeat(MAX_TITLE_LEN + 1)),
+            Err(format!("Title must be at most {} characters", MAX_TITLE_LEN))
+        );
+    }
+
+    #[test]
+    fn should_reject_edit_duplicating_another_title() {
+        let mut todos = sample_todos(3);
+        assert_eq!(
+            validate_edit(&todos, "1", "task 2"),
+            Err("Another todo already has this title".to_string())
+        );
+        todos[2].archived = true;
+        assert_eq!(validate_edit(&todos, "1", "Task 3"), Ok(()));
+    }
+
+    #[test]
+    fn should_trim_title_when_updating() {
+        let todos = sample_todos(1);
+        let new_todos = update_todo_title(&todos, "1", "  Hello  ", 42.0);
+        assert_eq!(new_todos[0].title, "Hello");
+    }
+
+    #[test]
+    fn should_reject_whitespace_only_title_update() {
+        let todos = sample_todos(1);
+        assert_eq!(update_todo_title(&todos, "1", "   ", 42.0), todos);
+        let padded = update_todo_title(&todos, "1", " Task 1 ", 42.0);
+        assert_eq!(padded, todos);
+    }
+
+    #[test]
+    fn should_cap_title_history_length() {
+        let mut todos = sample_todos(1);
+        for i in 0..MAX_TITLE_HISTORY + 5 {
+            todos = update_todo_title(&todos, "1", &format!("Title {}", i), i as f64);
+        }
+        let history = &todos[0].title_history;
+        assert_eq!(history.len(), MAX_TITLE_HISTORY);
+        assert_eq!(history.last(), Some(&(14.0, "Title 13".to_string())));
+        assert_eq!(history[0], (5.0, "Title 4".to_string()));
+    }
+
+    #[test]
+    fn should_treat_form_fields_as_text_entry() {
+        assert!(is_text_entry_tag("INPUT"));
+        assert!(is_text_entry_tag("textarea"));
+        assert!(is_text_entry_tag("SELECT"));
+        assert!(!is_text_entry_tag("BODY"));
+        assert!(!is_text_entry_tag("BUTTON"));
+    }
+
+    #[test]
+    fn should_filter_todos_completed_within_window() {
+        let now = 10_000_000.0;
+        let mut todos = sample_todos(4);
+        for todo in todos.iter_mut() {
+            todo.completed = true;
+        }
+        todos[0].completed_at = Some(now - 1_000.0);
+        todos[1].completed_at = Some(now - RECENT_WINDOW_MS);
+        todos[2].completed_at = Some(now - RECENT_WINDOW_MS - 1.0);
+        todos[3].completed_at = None;
+        assert_eq!(ids(&completed_within(&todos, RECENT_WINDOW_MS, now)), vec!["1", "2"]);
+    }
+
+    #[test]
+    fn should_filter_todos_completed_since_local_midnight() {
+        let offset = 2.0 * ONE_HOUR_MS;
+        let midnight = 10.0 * ONE_DAY_MS - offset;
+        let now = midnight + 9.0 * ONE_HOUR_MS;
+        let mut todos = sample_todos(4);
+        for todo in todos.iter_mut() {
+            todo.completed = true;
+        }
+        todos[0].completed_at = Some(midnight + 1.0);
+        todos[1].completed_at = Some(midnight);
+        todos[2].completed_at = Some(midnight - 1.0);
+        todos[3].completed_at = None;
+        assert_eq!(ids(&completed_today(&todos, now, offset)), vec!["1", "2"]);
+    }
+
+    #[test]
+    fn should_allow_completion_without_review_flag() {
+        let todo = Todo {
+            id: "1".to_string(),
+            ..Default::default()
+        };
+        assert!(can_complete(&todo));
+        let with_subtasks = Todo {
+            subtasks: vec![Subtask {
+                id: "s1".to_string(),
+                title: "Check".to_string(),
+                done: false,
+            }],
+            ..todo
+        };
+        assert!(can_complete(&with_subtasks));
+    }
+
+    #[test]
+    fn should_block_completion_until_review_confirmed() {
+        let pending = Todo {
+            id: "1".to_string(),
+            require_review: true,
+            ..Default::default()
+        };
+        assert!(!can_complete(&pending));
+        let with_subtasks = Todo {
+            subtasks: vec![Subtask {
+                id: "s1".to_string(),
+                title: "Check".to_string(),
+                done: true,
+            }],
+            ..pending.clone()
+        };
+        assert!(!can_complete(&with_subtasks));
+        let confirmed = Todo {
+            review_confirmed: true,
+            ..pending
+        };
+        assert!(can_complete(&confirmed));
+    }
+
+    #[test]
+    fn should_not_toggle_unconfirmed_review_todo() {
+        let todos = vec![Todo {
+            id: "1".to_string(),
+            require_review: true,
+            ..Default::default()
+        }];
+        assert_eq!(toggle_todo(&todos, "1", 5.0), todos);
+        let confirmed = confirm_review(&todos, "1", 5.0);
+        assert!(confirmed[0].completed);
+        assert!(confirmed[0].review_confirmed);
+        assert_eq!(confirmed[0].completed_at, Some(5.0));
+    }
+
+    #[test]
+    fn should_require_review_again_after_reopening() {
+        let todos = confirm_review(
+            &[Todo {
+                id: "1".to_string(),
+                require_review: true,
+                ..Default::default()
+            }],
+            "1",
+            5.0,
+        );
+        let reopened = toggle_todo(&todos, "1", 6.0);
+        assert!(!reopened[0].completed);
+        assert!(!can_complete(&reopened[0]));
+    }
+
+    #[test]
+    fn should_reset_confirmation_when_toggling_review_flag() {
+        let todos = vec![Todo {
+            id: "1".to_string(),
+            review_confirmed: true,
+            ..Default::default()
+        }];
+        let flagged = toggle_require_review(&todos, "1");
+        assert!(flagged[0].require_review);
+        assert!(!can_complete(&flagged[0]));
+    }
+
+    fn with_subtasks(done: &[bool]) -> Vec<Todo> {
+        vec![
+            Todo {
+                id: "1".to_string(),
+                subtasks: done
+                    .iter()
+                    .enumerate()
+                    .map(|(i, done)| Subtask {
+                        id: format!("s{}", i + 1),
+                        title: format!("Step {}", i + 1),
+                        done: *done,
+                    })
+                    .collect(),
+                ..Default::default()
+            },
+            Todo {
+                id: "2".to_string(),
+                ..Default::default()
+            },
+        ]
+    }
+
+    #[test]
+    fn should_reject_blank_subtask_titles() {
+        let todos = with_subtasks(&[false]);
+        assert_eq!(try_add_subtask(&todos, "1", ""), Err("Subtask can't be empty".to_string()));
+        assert_eq!(try_add_subtask(&todos, "1", "   "), Err("Subtask can't be empty".to_string()));
+        assert_eq!(
+            try_add_subtask(&todos, "1", &"x".repeat(MAX_TITLE_LEN + 1)),
+            Err(format!("Subtask must be at most {} characters", MAX_TITLE_LEN))
+        );
+        let added = try_add_subtask(&todos, "1", " Next step ").unwrap();
+        assert_eq!(added[0].subtasks.len(), 2);
+        assert_eq!(added[0].subtasks[1].title, "Next step");
+    }
+
+    #[test]
+    fn should_add_subtask_to_matching_todo_only() {
+        let todos = add_subtask(&with_subtasks(&[false]), "1", "  Write docs ");
+        assert_eq!(todos[0].subtasks.len(), 2);
+        assert_eq!(todos[0].subtasks[1].title, "Write docs");
+        assert!(!todos[0].subtasks[1].done);
+        assert_ne!(todos[0].subtasks[1].id, todos[0].subtasks[0].id);
+        assert!(todos[1].subtasks.is_empty());
+    }
+
+    #[test]
+    fn should_toggle_subtask_by_id() {
+        let todos = toggle_subtask(&with_subtasks(&[false, false]), "1", "s2");
+        assert!(!todos[0].subtasks[0].done);
+        assert!(todos[0].subtasks[1].done);
+        let todos = toggle_subtask(&todos, "1", "s2");
+        assert!(!todos[0].subtasks[1].done);
+    }
+
+    #[test]
+    fn should_delete_subtask_by_id() {
+        let todos = delete_subtask(&with_subtasks(&[false, true]), "1", "s1");
+        assert_eq!(todos[0].subtasks.len(), 1);
+        assert_eq!(todos[0].subtasks[0].id, "s2");
+        assert_eq!(delete_subtask(&todos, "2", "s2"), todos);
+    }
+
+    #[test]
+    fn should_have_no_progress_without_subtasks() {
+        assert_eq!(subtask_progress(&with_subtasks(&[])[0]), None);
+    }
+
+    #[test]
+    fn should_report_full_subtask_progress() {
+        assert_eq!(subtask_progress(&with_subtasks(&[true, true, true])[0]), Some((3, 3)));
+    }
+
+    #[test]
+    fn should_report_partial_subtask_progress() {
+        assert_eq!(
+            subtask_progress(&with_subtasks(&[true, false, true, false, false])[0]),
+            Some((2, 5))
+        );
+    }
+
+    #[test]
+    fn should_auto_complete_when_all_subtasks_done() {
+        let todos = complete_if_subtasks_done(&with_subtasks(&[true, true]), "1", 7.0);
+        assert!(todos[0].completed);
+        assert_eq!(todos[0].completed_at, Some(7.0));
+        let partial = complete_if_subtasks_done(&with_subtasks(&[true, false]), "1", 7.0);
+        assert!(!partial[0].completed);
+        let none = complete_if_subtasks_done(&with_subtasks(&[]), "1", 7.0);
+        assert!(!none[0].completed);
+    }
+
+    #[test]
+    fn should_filter_todos_with_blocked_reason() {
+        let mut todos = sample_todos(4);
+        todos[1].blocked_reason = Some("Waiting on design".to_string());
+        todos[2].blocked_reason = Some("   ".to_string());
+        todos[3].blocked_reason = Some("Vendor".to_string());
+        assert_eq!(ids(&filter_blocked(&todos)), vec!["2", "4"]);
+        assert!(filter_blocked(&sample_todos(2)).is_empty());
+    }
+
+    #[test]
+    fn should_set_and_clear_blocked_reason() {
+        let todos = set_blocked_reason(&sample_todos(2), "1", "  Waiting on review ");
+        assert_eq!(todos[0].blocked_reason.as_deref(), Some("Waiting on review"));
+        assert_eq!(todos[1].blocked_reason, None);
+        let cleared = set_blocked_reason(&todos, "1", " ");
+        assert_eq!(cleared[0].blocked_reason, None);
+    }
+
+    #[test]
+    fn should_open_accordion_group_when_none_expanded() {
+        assert_eq!(toggle_accordion(None, "#work"), Some("#work".to_string()));
+    }
+
+    #[test]
+    fn should_switch_accordion_to_other_group() {
+        assert_eq!(toggle_accordion(Some("#work"), "#home"), Some("#home".to_string()));
+    }
+
+    #[test]
+    fn should_collapse_open_accordion_group() {
+        assert_eq!(toggle_accordion(Some("#work"), "#work"), None);
+    }
+
+    #[test]
+    fn should_group_todos_by_tag_with_untagged_last() {
+        let mut todos = sample_todos(3);
+        todos[0].tags = vec!["work".to_string(), "home".to_string()];
+        todos[2].tags = vec!["home".to_string()];
+        let groups = group_by_tag(&todos);
+        let summary: Vec<(&str, Vec<&str>)> = groups
+            .iter()
+            .map(|(key, group)| (key.as_str(), ids(group)))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                ("#home", vec!["1", "3"]),
+                ("#work", vec!["1"]),
+                ("Untagged", vec!["2"]),
+            ]
+        );
+    }
+
+    #[test]
+    fn should_toggle_expanded_row_without_affecting_others() {
+        let expanded = toggle_expanded(&HashSet::new(), "1");
+        let expanded = toggle_expanded(&expanded, "2");
+        assert!(expanded.contains("1") && expanded.contains("2"));
+        let collapsed = toggle_expanded(&expanded, "1");
+        assert!(!collapsed.contains("1"));
+        assert!(collapsed.contains("2"));
+    }
+
+    fn recurring(recurrence: Recurrence, due_at: f64) -> Todo {
+        Todo {
+            id: "1".to_string(),
+            title: "Water plants".to_string(),
+            completed: true,
+            completed_at: Some(due_at),
+            due_at: Some(due_at),
+            recurrence: Some(recurrence),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn should_assign_and_clear_category_on_single_todo() {
+        let todos = sample_todos(2);
+        let work = Category {
+            name: "Work".to_string(),
+            color: "#ff0000".to_string(),
+        };
+        let assigned = assign_category(&todos, "1", Some(work.clone()));
+        assert_eq!(assigned[0].category, Some(work));
+        assert_eq!(assigned[1], todos[1]);
+        let cleared = assign_category(&assigned, "1", None);
+        assert_eq!(cleared, todos);
+    }
+
+    #[test]
+    fn should_add_only_new_named_categories_with_valid_colors() {
+        let categories = add_category(&[], " Home ", "#00ff00");
+        assert_eq!(categories[0].name, "Home");
+        assert_eq!(add_category(&categories, "home", "#0000ff"), categories);
+        assert_eq!(add_category(&categories, "  ", "#0000ff"), categories);
+        assert_eq!(add_category(&categories, "Work", "red;x"), categories);
+    }
+
+    #[test]
+    fn should_use_neutral_border_for_uncategorized_todos() {
+        assert_eq!(category_border_color(None), NEUTRAL_BORDER_COLOR);
+        let category = Category {
+            name: "Work".to_string(),
+            color: "#abc".to_string(),
+        };
+        assert_eq!(category_border_color(Some(&category)), "#abc");
+    }
+
+    #[test]
+    fn should_bucket_todos_by_due_date() {
+        let now = 10.0 * ONE_DAY_MS + 12.0 * ONE_HOUR_MS;
+        let mut todos = sample_todos(6);
+        todos[0].due_at = Some(now - ONE_DAY_MS);
+        todos[1].due_at = Some(now + ONE_HOUR_MS);
+        todos[2].due_at = Some(now + ONE_DAY_MS);
+        todos[4].due_at = Some(now - ONE_HOUR_MS);
+        todos[5].due_at = Some(now - ONE_DAY_MS);
+        todos[5].completed = true;
+        let groups = group_by_due(&todos, now, 0.0);
+        assert_eq!(ids(&groups.overdue), vec!["1"]);
+        assert_eq!(ids(&groups.today), vec!["2", "5", "6"]);
+        assert_eq!(ids(&groups.upcoming), vec!["3"]);
+        assert_eq!(ids(&groups.no_date), vec!["4"]);
+    }
+
+    #[test]
+    fn should_group_date_picked_todo_due_today_under_today() {
+        let offset = -5.0 * ONE_HOUR_MS;
+        let now = 10.0 * ONE_DAY_MS + 15.0 * ONE_HOUR_MS;
+        let mut todos = sample_todos(2);
+        todos[0].due_at = parse_date_input("1970-01-11", offset);
+        todos[1].due_at = parse_date_input("1970-01-10", offset);
+        let groups = group_by_due(&todos, now, offset);
+        assert_eq!(ids(&groups.today), vec!["1"]);
+        assert_eq!(ids(&groups.overdue), vec!["2"]);
+    }
+
+    #[test]
+    fn should_flag_only_active_todos_due_before_today_as_overdue() {
+        let today = 10.0 * ONE_DAY_MS;
+        let now = today + 9.0 * ONE_HOUR_MS;
+        let mut todo = titled("1", "Task");
+        assert!(!is_overdue(&todo, now, 0.0));
+        todo.due_at = Some(today);
+        assert!(!is_overdue(&todo, now, 0.0));
+        todo.due_at = Some(today - 1.0);
+        assert!(is_overdue(&todo, now, 0.0));
+        todo.completed = true;
+        assert!(!is_overdue(&todo, now, 0.0));
+    }
+
+    #[test]
+    fn should_find_id_of_newly_added_todo() {
+        let todos = sample_todos(2);
+        let mut added = vec![titled("new", "New")];
+        added.extend(todos.iter().cloned());
+        assert_eq!(new_todo_id(&todos, &added), Some("new".to_string()));
+        assert_eq!(new_todo_id(&todos, &todos), None);
+    }
+
+    #[test]
+    fn should_deserialize_todo_without_due_date() {
+        let todo: Todo = serde_json::from_str(r#"{"id":"1","title":"Task","completed":false}"#).unwrap();
+        assert_eq!(todo.due_at, None);
+    }
+
+    #[test]
+    fn should_exclude_completed_todos_from_overdue() {
+        let now = 10.0 * ONE_DAY_MS;
+        let mut todos = sample_todos(2);
+        todos[0].due_at = Some(now - 3.0 * ONE_DAY_MS);
+        todos[0].completed = true;
+        todos[1].due_at = Some(now - 3.0 * ONE_DAY_MS);
+        let groups = group_by_due(&todos, now, 0.0);
+        assert_eq!(ids(&groups.overdue), vec!["2"]);
+        assert_eq!(ids(&groups.today), vec!["1"]);
+    }
+
+    #[test]
+    fn should_not_spawn_occurrence_without_recurrence() {
+        assert_eq!(next_occurrence(&sample_todos(1)[0], 0.0, 0.0), None);
+    }
+
+    #[test]
+    fn should_roll_daily_occurrence_forward_one_day() {
+        let due_at = days_from_civil(2024, 3, 10) as f64 * ONE_DAY_MS;
+        let next = next_occurrence(&recurring(Recurrence::Daily, due_at), due_at, 0.0).unwrap();
+        assert_eq!(next.due_at, Some(due_at + ONE_DAY_MS));
+        assert!(!next.completed);
+        assert_eq!(next.completed_at, None);
+        assert_ne!(next.id, "1");
+        assert_eq!(next.title, "Water plants");
+        assert_eq!(next.recurrence, Some(Recurrence::Daily));
+        assert_eq!(next.created_at, Some(due_at));
+    }
+
+    #[test]
+    fn should_carry_planning_fields_into_next_occurrence() {
+        let due_at = 10.0 * ONE_DAY_MS;
+        let todo = Todo {
+            context: Some("home".to_string()),
+            category: Some(Category {
+                name: "Chores".to_string(),
+                color: "#0a0".to_string(),
+            }),
+            estimate_minutes: Some(15),
+            actual_minutes: Some(20),
+            blocked_reason: Some("Need soil".to_string()),
+            review_confirmed: true,
+            snooze_count: 2,
+            ..recurring(Recurrence::Daily, due_at)
+        };
+        let next = next_occurrence(&todo, due_at, 0.0).unwrap();
+        assert_eq!(next.context, todo.context);
+        assert_eq!(next.category, todo.category);
+        assert_eq!(next.estimate_minutes, Some(15));
+        assert_eq!(next.blocked_reason, todo.blocked_reason);
+        assert_eq!(next.actual_minutes, None);
+        assert!(!next.review_confirmed);
+        assert_eq!(next.snooze_count, 0);
+    }
+
+    #[test]
+    fn should_roll_weekly_occurrence_forward_one_week() {
+        let due_at = days_from_civil(2024, 12, 28) as f64 * ONE_DAY_MS;
+        let next = next_occurrence(&recurring(Recurrence::Weekly, due_at), due_at, 0.0).unwrap();
+        assert_eq!(next.due_at, Some(days_from_civil(2025, 1, 4) as f64 * ONE_DAY_MS));
+    }
+
+    #[test]
+    fn should_roll_monthly_occurrence_with_month_end_clamping() {
+        let offset = 2.0 * 60.0 * 60.0 * 1000.0;
+        let local_midnight = |y, m, d| days_from_civil(y, m, d) as f64 * ONE_DAY_MS - offset;
+        let next = |due_at| {
+            next_occurrence(&recurring(Recurrence::Monthly, due_at), due_at, offset)
+                .unwrap()
+                .due_at
+        };
+        assert_eq!(next(local_midnight(2024, 3, 15)), Some(local_midnight(2024, 4, 15)));
+        assert_eq!(next(local_midnight(2024, 1, 31)), Some(local_midnight(2024, 2, 29)));
+        assert_eq!(next(local_midnight(2023, 1, 31)), Some(local_midnight(2023, 2, 28)));
+        assert_eq!(next(local_midnight(2024, 12, 31)), Some(local_midnight(2025, 1, 31)));
+    }
+
+    #[test]
+    fn should_round_trip_civil_dates() {
+        for (y, m, d) in [(1970, 1, 1), (2000, 2, 29), (2024, 12, 31), (1969, 7, 20)] {
+            assert_eq!(civil_from_days(days_from_civil(y, m, d)), (y, m, d));
+        }
+    }
+
+    #[test]
+    fn should_spawn_next_occurrence_only_when_just_completed() {
+        let due_at = 10.0 * ONE_DAY_MS;
+        let done = recurring(Recurrence::Daily, due_at);
+        let active = Todo {
+            completed: false,
+            completed_at: None,
+            ..done.clone()
+        };
+        let spawned = spawn_recurrences(&[active], std::slice::from_ref(&done), due_at, 0.0);
+        assert_eq!(spawned.len(), 2);
+        assert_eq!(spawned[0], done);
+        assert!(!spawned[1].completed);
+        assert_eq!(spawned[1].due_at, Some(due_at + ONE_DAY_MS));
+        let unchanged = vec![done];
+        assert_eq!(spawn_recurrences(&unchanged, &unchanged, due_at, 0.0), unchanged);
+    }
+
+    #[test]
+    fn should_queue_only_active_todos_for_review() {
+        let mut todos = sample_todos(4);
+        todos[1].completed = true;
+        todos[2].archived = true;
+        assert_eq!(review_queue(&todos), vec!["1".to_string(), "4".to_string()]);
+        assert!(review_queue(&[]).is_empty());
+    }
+
+    #[test]
+    fn should_order_review_queue_oldest_first() {
+        let mut todos = sample_todos(4);
+        todos[0].created_at = Some(300.0);
+        todos[1].created_at = Some(100.0);
+        todos[2].created_at = Some(200.0);
+        assert_eq!(
+            review_queue(&todos),
+            vec!["4".to_string(), "2".to_string(), "3".to_string(), "1".to_string()]
+        );
+    }
+
+    #[test]
+    fn should_stamp_created_at_on_new_todo() {
+        let new_todos = create_new_todo(&[], "Task".to_string(), false, 42.0);
+        assert_eq!(new_todos[0].created_at, Some(42.0));
+    }
+
+    #[test]
+    fn should_paginate_full_and_partial_pages() {
+        let todos = sample_todos(5);
+        assert_eq!(ids(paginate(&todos, 0, 2)), vec!["1", "2"]);
+        assert_eq!(ids(paginate(&todos, 1, 2)), vec!["3", "4"]);
+        assert_eq!(ids(paginate(&todos, 2, 2)), vec!["5"]);
+        assert_eq!(ids(paginate(&todos, 0, 5)), vec!["1", "2", "3", "4", "5"]);
+    }
+
+    #[test]
+    fn should_clamp_out_of_range_page_to_last() {
+        let todos = sample_todos(4);
+        assert_eq!(ids(paginate(&todos, 9, 2)), vec!["3", "4"]);
+        assert_eq!(page_count(4, 2), 2);
+        assert_eq!(page_count(5, 2), 3);
+    }
+
+    #[test]
+    fn should_start_focus_at_list_edges() {
+        assert_eq!(move_focus(None, 1, 3), Some(0));
+        assert_eq!(move_focus(None, -1, 3), Some(2));
+        assert_eq!(move_focus(None, 1, 0), None);
+        assert_eq!(move_focus(Some(1), 1, 0), None);
+    }
+
+    #[test]
+    fn should_clamp_focus_within_visible_rows() {
+        assert_eq!(move_focus(Some(0), 1, 3), Some(1));
+        assert_eq!(move_focus(Some(2), 1, 3), Some(2));
+        assert_eq!(move_focus(Some(0), -1, 3), Some(0));
+        assert_eq!(move_focus(Some(2), -1, 3), Some(1));
+        assert_eq!(move_focus(Some(9), -1, 3), Some(1));
+        assert_eq!(move_focus(Some(9), 1, 3), Some(2));
+    }
+
+    #[test]
+    fn should_paginate_empty_list() {
+        assert!(paginate(&[], 0, PAGE_SIZE).is_empty());
+        assert!(paginate(&[], 3, PAGE_SIZE).is_empty());
+        assert_eq!(page_count(0, PAGE_SIZE), 1);
+    }
+
+    #[test]
+    fn should_project_completion_from_position_and_rate() {
+        let now = 10.0 * ONE_DAY_MS;
+        assert_eq!(projected_completion(0, 1.0, now), Some(now + ONE_DAY_MS));
+        assert_eq!(projected_completion(3, 2.0, now), Some(now + 2.0 * ONE_DAY_MS));
+    }
+
+    #[test]
+    fn should_not_project_completion_with_zero_rate() {
+        assert_eq!(projected_completion(0, 0.0, 0.0), None);
+        assert_eq!(projected_completion(5, 0.0, 0.0), None);
+    }
+
+    #[test]
+    fn should_measure_completions_per_day_over_last_week() {
+        let now = 30.0 * ONE_DAY_MS;
+        let mut todos = sample_todos(4);
+        for (todo, days_ago) in todos.iter_mut().zip([1.0, 3.0, 6.0, 8.0]) {
+            todo.completed = true;
+            todo.completed_at = Some(now - days_ago * ONE_DAY_MS);
+        }
+        assert_eq!(completions_per_day(&todos, now), 3.0 / 7.0);
+    }
+
+    #[test]
+    fn should_bucket_completions_by_days_ago() {
+        let now = 30.0 * ONE_DAY_MS + 12.0 * ONE_HOUR_MS;
+        let mut todos = sample_todos(6);
+        for (todo, completed_at) in todos.iter_mut().zip([
+            Some(now - ONE_HOUR_MS),
+            Some(now - ONE_DAY_MS),
+            Some(now - 6.0 * ONE_DAY_MS),
+            Some(now - 7.0 * ONE_DAY_MS),
+            Some(now + ONE_DAY_MS),
+            None,
+        ]) {
+            todo.completed = completed_at.is_some();
+            todo.completed_at = completed_at;
+        }
+        assert_eq!(completions_by_day(&todos, now, 0.0), [1, 1, 0, 0, 0, 0, 1]);
+    }
+
+    #[test]
+    fn should_count_completion_at_day_boundary_in_the_new_day() {
+        let midnight = 30.0 * ONE_DAY_MS;
+        let now = midnight + 12.0 * ONE_HOUR_MS;
+        let mut todos = sample_todos(2);
+        for todo in todos.iter_mut() {
+            todo.completed = true;
+        }
+        todos[0].completed_at = Some(midnight);
+        todos[1].completed_at = Some(midnight - 1.0);
+        assert_eq!(completions_by_day(&todos, now, 0.0), [1, 1, 0, 0, 0, 0, 0]);
+        assert_eq!(completions_by_day(&todos, now, ONE_HOUR_MS), [2, 0, 0, 0, 0, 0, 0]);
+    }
+
+    #[test]
+    fn should_not_count_reopened_todo_with_stale_completed_at() {
+        let now = 30.0 * ONE_DAY_MS + 12.0 * ONE_HOUR_MS;
+        let mut todos = sample_todos(2);
+        todos[0].completed = true;
+        todos[0].completed_at = Some(now - ONE_HOUR_MS);
+        todos[1].completed = false;
+        todos[1].completed_at = Some(now - ONE_HOUR_MS);
+        assert_eq!(completions_by_day(&todos, now, 0.0), [1, 0, 0, 0, 0, 0, 0]);
+    }
+
+    #[test]
+    fn should_format_known_epoch_as_local_date_time() {
+        assert_eq!(format_local_timestamp(0.0, 0.0), "1970-01-01 00:00");
+        assert_eq!(format_local_timestamp(1_700_000_000_000.0, 0.0), "2023-11-14 22:13");
+        assert_eq!(format_local_timestamp(1_700_000_000_000.0, 2.0 * ONE_HOUR_MS), "2023-11-15 00:13");
+    }
+
+    #[test]
+    fn should_show_unknown_for_missing_timestamps() {
+        let format = |ms: f64| format_local_timestamp(ms, 0.0);
+        assert_eq!(
+            timestamp_tooltip(None, None, format),
+            "Created: unknown / Updated: unknown"
+        );
+        assert_eq!(
+            timestamp_tooltip(Some(0.0), None, format),
+            "Created: 1970-01-01 00:00 / Updated: unknown"
+        );
+    }
+
+    #[test]
+    fn should_stamp_only_changed_todos_as_updated() {
+        let todos = sample_todos(3);
+        let mut changed = todos.clone();
+        changed[1].title = "Renamed".to_string();
+        changed.push(titled("4", "New"));
+        let stamped = stamp_updated(&todos, &changed, 9.0);
+        assert_eq!(stamped[0].updated_at, None);
+        assert_eq!(stamped[1].updated_at, Some(9.0));
+        assert_eq!(stamped[2].updated_at, None);
+        assert_eq!(stamped[3].updated_at, None);
+    }
+
+    #[test]
+    fn should_schedule_edit_badge_expiry_for_earliest_recent_edit() {
+        let mut todos = sample_todos(3);
+        todos[0].updated_at = Some(1_000.0);
+        todos[1].updated_at = Some(4_000.0);
+        todos[2].updated_at = Some(1_000.0 - EDITED_WINDOW_MS);
+        assert_eq!(ms_until_edit_badge_expires(&todos, 5_000.0, EDITED_WINDOW_MS), Some(EDITED_WINDOW_MS - 4_000.0));
+        assert_eq!(ms_until_edit_badge_expires(&todos, 1_000.0 + EDITED_WINDOW_MS, EDITED_WINDOW_MS), Some(3_000.0));
+        assert_eq!(ms_until_edit_badge_expires(&sample_todos(2), 5_000.0, EDITED_WINDOW_MS), None);
+    }
+
+    #[test]
+    fn should_round_trip_todos_through_memory_store() {
+        let store = MemoryStore::default();
+        let todos = sample_todos(3);
+        store.save("todos:default", &todos).unwrap();
+        assert_eq!(store.load("todos:default").unwrap(), todos);
+        assert!(matches!(store.load("todos:work"), Err(StorageError::KeyNotFound(_))));
+    }
+
+    #[test]
+    fn should_load_missing_key_from_store_as_empty_list() {
+        let store = MemoryStore::default();
+        assert!(load_todos_from(&store, "todos:default").unwrap().is_empty());
+        store.save("todos:default", &sample_todos(2)).unwrap();
+        assert_eq!(ids(&load_todos_from(&store, "todos:default").unwrap()), vec!["1", "2"]);
+    }
+
+    #[test]
+    fn should_round_trip_todos_through_fallback_store() {
+        assert!(!fallback_active());
+        enable_fallback_store("todos:default", &sample_todos(2));
+        assert!(fallback_active());
+        enable_fallback_store("todos:work", &sample_todos(1));
+        let store = fallback_store().unwrap();
+        assert_eq!(store.load("todos:default").unwrap(), sample_todos(2));
+        assert_eq!(store.load("todos:work").unwrap(), sample_todos(1));
+        clear_fallback_store();
+        assert!(!fallback_active());
+    }
+
+    #[test]
+    fn should_detect_conflict_only_when_stored_revision_is_newer() {
+        assert!(!has_conflict(0, 0));
+        assert!(!has_conflict(3, 3));
+        assert!(!has_conflict(2, 3));
+        assert!(has_conflict(4, 3));
+        assert_eq!(next_revision(3), 4);
+        assert_eq!(next_revision(u64::MAX), u64::MAX);
+    }
+
+    #[test]
+    fn should_bump_revision_on_save_and_track_it_as_read() {
+        let store = MemoryStore::default();
+        assert!(load_todos_from(&store, "todos:default").unwrap().is_empty());
+        save_with_revision(&store, "todos:default", &sample_todos(1)).unwrap();
+        assert_eq!(store.load_revision("todos:default"), 1);
+        assert!(!has_conflict(store.load_revision("todos:default"), READ_REVISION.with(Cell::get)));
+        store.save_revision("todos:default", 2).unwrap();
+        assert!(has_conflict(store.load_revision("todos:default"), READ_REVISION.with(Cell::get)));
+    }
+
+    #[test]
+    fn should_share_memory_store_between_clones() {
+        let store = MemoryStore::default();
+        store.clone().save("todos:default", &sample_todos(1)).unwrap();
+        assert_eq!(store.load("todos:default").unwrap(), sample_todos(1));
+    }
+
+    #[test]
+    fn should_flag_recent_edits_only_within_window() {
+        let mut todo = titled("1", "Task");
+        assert!(!was_recently_edited(&todo, 1_000.0, EDITED_WINDOW_MS));
+        todo.updated_at = Some(1_000.0);
+        assert!(was_recently_edited(&todo, 1_000.0, EDITED_WINDOW_MS));
+        assert!(was_recently_edited(&todo, 1_000.0 + EDITED_WINDOW_MS - 1.0, EDITED_WINDOW_MS));
+        assert!(!was_recently_edited(&todo, 1_000.0 + EDITED_WINDOW_MS, EDITED_WINDOW_MS));
+    }
+
+    #[test]
+    fn should_sum_estimates_and_actuals_across_active_todos() {
+        let mut todos = sample_todos(5);
+        todos[0].estimate_minutes = Some(30);
+        todos[0].actual_minutes = Some(45);
+        todos[1].estimate_minutes = Some(15);
+        todos[2].actual_minutes = Some(10);
+        todos[3].estimate_minutes = Some(60);
+        todos[3].completed = true;
+        todos[4].estimate_minutes = Some(20);
+        todos[4].archived = true;
+        assert_eq!(sum_estimates(&todos), (45, 55));
+        assert_eq!(sum_estimates(&[]), (0, 0));
+        todos[0].estimate_minutes = Some(u32::MAX);
+        assert_eq!(sum_estimates(&todos).0, u32::MAX);
+    }
+
+    #[test]
+    fn should_set_estimate_and_actual_minutes() {
+        let todos = set_estimate_minutes(&sample_todos(2), "1", Some(25));
+        let todos = set_actual_minutes(&todos, "1", Some(40));
+        assert_eq!((todos[0].estimate_minutes, todos[0].actual_minutes), (Some(25), Some(40)));
+        assert_eq!(todos[1], sample_todos(2)[1]);
+        assert_eq!(set_estimate_minutes(&todos, "1", None)[0].estimate_minutes, None);
+    }
+
+    #[test]
+    fn should_label_weekdays() {
+        assert_eq!(weekday_label(0.0, 0.0), "Thu");
+        assert_eq!(weekday_label(days_from_civil(2024, 3, 15) as f64 * ONE_DAY_MS, 0.0), "Fri");
+    }
+
+    #[test]
+    fn should_start_blank_without_stored_draft() {
+        assert_eq!(decode_draft(None), "");
+        assert_eq!(decode_draft(Some("")), "");
+    }
+
+    #[test]
+    fn should_restore_stored_draft_verbatim() {
+        assert_eq!(decode_draft(Some("Buy milk #errands")), "Buy milk #errands");
+    }
+
+    #[test]
+    fn should_clamp_saved_scroll_to_content_height() {
+        assert_eq!(clamp_scroll(120, 1000, 400), 120);
+        assert_eq!(clamp_scroll(900, 1000, 400), 600);
+        assert_eq!(clamp_scroll(300, 200, 400), 0);
+        assert_eq!(clamp_scroll(-5, 1000, 400), 0);
+    }
+
+    #[test]
+    fn should_ask_for_confirmation_before_clearing_all() {
+        let todos = sample_todos(3);
+        let (confirming, cleared) = advance_clear_all(false, &todos);
+        assert!(confirming);
+        assert_eq!(cleared, None);
+        let (confirming, cleared) = advance_clear_all(confirming, &todos);
+        assert!(!confirming);
+        assert_eq!(cleared, Some(Vec::new()));
+    }
+
+    #[test]
+    fn should_need_confirm_only_above_threshold() {
+        assert!(!needs_confirm(0));
+        assert!(!needs_confirm(CONFIRM_THRESHOLD));
+        assert!(needs_confirm(CONFIRM_THRESHOLD + 1));
+    }
+
+    #[test]
+    fn should_clear_single_completed_todo_without_prompt() {
+        let mut todos = sample_todos(3);
+        todos[1].completed = true;
+        let (confirming, cleared) = advance_clear_completed(false, &todos);
+        assert!(!confirming);
+        assert_eq!(ids(&cleared.unwrap()), vec!["1", "3"]);
+    }
+
+    #[test]
+    fn should_confirm_before_clearing_several_completed_todos() {
+        let mut todos = sample_todos(4);
+        todos[0].completed = true;
+        todos[2].completed = true;
+        todos[3].completed = true;
+        todos[3].archived = true;
+        let (confirming, cleared) = advance_clear_completed(false, &todos);
+        assert!(confirming);
+        assert_eq!(cleared, None);
+        let (confirming, cleared) = advance_clear_completed(confirming, &todos);
+        assert!(!confirming);
+        assert_eq!(ids(&cleared.unwrap()), vec!["2", "4"]);
+        assert_eq!(advance_clear_completed(false, &sample_todos(2)), (false, None));
+    }
+
+    #[test]
+    fn should_not_clear_already_empty_list() {
+        assert_eq!(advance_clear_all(false, &[]), (false, None));
+        assert_eq!(advance_clear_all(true, &[]), (false, None));
+    }
+
+    #[test]
+    fn should_move_scattered_selection_to_top_in_order() {
+        let selected: HashSet<String> = ["4", "2", "5"].iter().map(|id| id.to_string()).collect();
+        assert_eq!(
+            ids(&move_selection_to_edge(&sample_todos(5), &selected, true)),
+            vec!["2", "4", "5", "1", "3"]
+        );
+    }
+
+    #[test]
+    fn should_move_scattered_selection_to_bottom_in_order() {
+        let selected: HashSet<String> = ["1", "3", "4"].iter().map(|id| id.to_string()).collect();
+        assert_eq!(
+            ids(&move_selection_to_edge(&sample_todos(5), &selected, false)),
+            vec!["2", "5", "1", "3", "4"]
+        );
+    }
+
+    #[test]
+    fn should_put_completed_todos_last_preserving_group_order() {
+        let mut todos = sample_todos(5);
+        todos[0].completed = true;
+        todos[3].completed = true;
+        assert_eq!(ids(&partition_completed(&todos)), vec!["2", "3", "5", "1", "4"]);
+    }
+
+    #[test]
+    fn should_leave_uniform_lists_unchanged_when_partitioning() {
+        let active = sample_todos(3);
+        assert_eq!(partition_completed(&active), active);
+        let completed: Vec<Todo> = sample_todos(3)
+            .into_iter()
+            .map(|todo| Todo {
+                completed: true,
+                ..todo
+            })
+            .collect();
+        assert_eq!(partition_completed(&completed), completed);
+    }
+
+    #[test]
+    fn should_extract_leading_context_token() {
+        assert_eq!(
+            extract_context("@errands buy milk"),
+            ("buy milk".to_string(), Some("errands".to_string()))
+        );
+        assert_eq!(
+            extract_context("  @home   water plants "),
+            ("water plants".to_string(), Some("home".to_string()))
+        );
+    }
+
+    #[test]
+    fn should_not_extract_mid_string_context_token() {
+        assert_eq!(extract_context("email bob@work.com"), ("email bob@work.com".to_string(), None));
+        assert_eq!(extract_context("call @home later"), ("call @home later".to_string(), None));
+    }
+
+    #[test]
+    fn should_leave_input_without_context_unchanged() {
+        assert_eq!(extract_context("buy milk"), ("buy milk".to_string(), None));
+        assert_eq!(extract_context("@home"), ("@home".to_string(), None));
+        assert_eq!(extract_context("@ home"), ("@ home".to_string(), None));
+    }
+
+    #[test]
+    fn should_store_context_and_filter_by_it() {
+        let todos = create_new_todo(&[], "@home water plants #garden".to_string(), false, 0.0);
+        let todos = create_new_todo(&todos, "@work ship release".to_string(), false, 0.0);
+        let todos = create_new_todo(&todos, "read book".to_string(), false, 0.0);
+        assert_eq!(todos[0].title, "water plants");
+        assert_eq!(todos[0].context.as_deref(), Some("home"));
+        assert_eq!(todos[0].tags, vec!["garden".to_string()]);
+        assert_eq!(contexts(&todos), vec!["home".to_string(), "work".to_string()]);
+        let at_work = filter_by_context(&todos, "work");
+        assert_eq!(at_work.len(), 1);
+        assert_eq!(at_work[0].title, "ship release");
+    }
+
+    #[test]
+    fn should_collect_diagnostics_for_sample_list() {
+        let mut todos = sample_todos(3);
+        todos[2].archived = true;
+        let diagnostics = collect_diagnostics(&todos);
+        assert_eq!(diagnostics.item_count, 3);
+        assert_eq!(diagnostics.archived_count, 1);
+        assert_eq!(diagnostics.serialized_bytes, serde_json::to_string(&todos).unwrap().len());
+        assert_eq!(diagnostics.schema_version, SCHEMA_VERSION);
+        assert_eq!(collect_diagnostics(&[]).serialized_bytes, 2);
+    }
+
+    #[test]
+    fn should_detect_diag_query_parameter() {
+        assert!(is_diag_query("?diag=1"));
+        assert!(is_diag_query("?foo=bar&diag=1"));
+        assert!(!is_diag_query("?diag=0"));
+        assert!(!is_diag_query("?diag=10"));
+        assert!(!is_diag_query(""));
+    }
+
+    #[test]
+    fn should_count_empty_list_for_every_filter() {
+        assert_eq!(filter_counts(&[]), (0, 0, 0));
+    }
+
+    #[test]
+    fn should_only_allow_all_filter_on_empty_list() {
+        let counts = filter_counts(&[]);
+        assert!(is_filter_selectable(Filter::All, counts));
+        assert!(!is_filter_selectable(Filter::Active, counts));
+        assert!(!is_filter_selectable(Filter::Completed, counts));
+    }
+
+    #[test]
+    fn should_disable_filters_without_matches_in_mixed_list() {
+        let mut todos = sample_todos(3);
+        assert!(!is_filter_selectable(Filter::Completed, filter_counts(&todos)));
+        todos[0].completed = true;
+        let counts = filter_counts(&todos);
+        assert!(is_filter_selectable(Filter::All, counts));
+        assert!(is_filter_selectable(Filter::Active, counts));
+        assert!(is_filter_selectable(Filter::Completed, counts));
+        for todo in todos.iter_mut() {
+            todo.completed = true;
+        }
+        assert!(!is_filter_selectable(Filter::Active, filter_counts(&todos)));
+    }
+
+    #[test]
+    fn should_count_mixed_list_per_filter() {
+        let mut todos = sample_todos(5);
+        todos[1].completed = true;
+        todos[3].completed = true;
+        todos[4].archived = true;
+        assert_eq!(filter_counts(&todos), (4, 2, 2));
+    }
+
+    #[test]
+    fn should_format_plain_doc_title_without_active_todos() {
+        assert_eq!(format_doc_title(0), "Todos");
+    }
+
+    #[test]
+    fn should_format_doc_title_with_active_count() {
+        assert_eq!(format_doc_title(1), "Todos (1 active)");
+        assert_eq!(format_doc_title(3), "Todos (3 active)");
+    }
+
+    #[test]
+    fn should_count_only_active_unarchived_todos() {
+        let mut todos = sample_todos(4);
+        todos[0].completed = true;
+        todos[1].archived = true;
+        assert_eq!(count_active(&todos), 2);
+    }
+
+    #[test]
+    fn should_toggle_star_by_id() {
+        let todos = sample_todos(2);
+        let starred = toggle_star(&todos, "1");
+        assert!(starred[0].starred);
+        assert!(!starred[1].starred);
+        assert_eq!(starred[0].title, "Task 1");
+        let unstarred = toggle_star(&starred, "1");
+        assert!(!unstarred[0].starred);
+    }
+
+    #[test]
+    fn should_toggle_pin_only_for_matching_id() {
+        let todos = sample_todos(2);
+        let pinned = toggle_pin(&todos, "2");
+        assert!(!pinned[0].pinned);
+        assert!(pinned[1].pinned);
+        assert!(!toggle_pin(&pinned, "2")[1].pinned);
+    }
+
+    #[test]
+    fn should_float_pinned_todos_preserving_group_order() {
+        let mut todos = sample_todos(5);
+        todos[1].pinned = true;
+        todos[4].pinned = true;
+        assert_eq!(ids(&apply_pins(&todos)), vec!["2", "5", "1", "3", "4"]);
+        assert_eq!(apply_pins(&sample_todos(3)), sample_todos(3));
+    }
+
+    #[test]
+    fn should_apply_pins_after_status_filter() {
+        let mut todos = sample_todos(4);
+        todos[1].completed = true;
+        todos[1].pinned = true;
+        todos[3].pinned = true;
+        let active: Vec<Todo> = todos
+            .iter()
+            .filter(|todo| Filter::Active.matches(todo))
+            .cloned()
+            .collect();
+        assert_eq!(ids(&apply_pins(&active)), vec!["4", "1", "3"]);
+        let completed: Vec<Todo> = todos
+            .iter()
+            .filter(|todo| Filter::Completed.matches(todo))
+            .cloned()
+            .collect();
+        assert_eq!(ids(&apply_pins(&completed)), vec!["2"]);
+    }
+
+    #[test]
+    fn should_persist_pinned_flag_and_default_to_unpinned() {
+        let mut todo = sample_todos(1).remove(0);
+        todo.pinned = true;
+        let decoded: Todo = serde_json::from_str(&serde_json::to_string(&todo).unwrap()).unwrap();
+        assert!(decoded.pinned);
+        let legacy: Todo = serde_json::from_str(r#"{"id":"1","title":"Old","completed":false}"#).unwrap();
+        assert!(!legacy.pinned);
+    }
+
+    #[test]
+    fn should_sort_starred_first_preserving_relative_order() {
+        let mut todos = sample_todos(5);
+        todos[1].starred = true;
+        todos[3].starred = true;
+        assert_eq!(
+            ids(&sort_todos(&todos, SortMode::Starred, 0.0)),
+            vec!["2", "4", "1", "3", "5"]
+        );
+    }
+
+    #[test]
+    fn should_sort_by_due_date_with_undated_last() {
+        let mut todos = sample_todos(4);
+        todos[0].due_at = Some(30.0);
+        todos[2].due_at = Some(10.0);
+        todos[3].due_at = Some(20.0);
+        assert_eq!(
+            ids(&sort_todos(&todos, SortMode::Due, 0.0)),
+            vec!["3", "4", "1", "2"]
+        );
+    }
+
+    #[test]
+    fn should_sort_alphabetically_ignoring_case() {
+        let todos = vec![
+            titled("1", "banana"),
+            titled("2", "Apple"),
+            titled("3", "cherry"),
+            titled("4", "apricot"),
+        ];
+        assert_eq!(ids(&sort_alphabetical(&todos)), vec!["2", "4", "1", "3"]);
+        assert_eq!(ids(&sort_todos(&todos, SortMode::Alphabetical, 0.0)), vec!["2", "4", "1", "3"]);
+        assert_eq!(ids(&todos), vec!["1", "2", "3", "4"]);
+    }
+
+    #[test]
+    fn should_match_everything_with_empty_query() {
+        assert_eq!(fuzzy_match("Buy milk", ""), Some(0));
+        assert_eq!(fuzzy_match("Buy milk", "   "), Some(0));
+        assert_eq!(fuzzy_filter(&sample_todos(3), ""), sample_todos(3));
+    }
+
+    #[test]
+    fn should_reject_titles_missing_query_subsequence() {
+        assert_eq!(fuzzy_match("Buy milk", "mlkb"), None);
+        assert_eq!(fuzzy_match("Buy milk", "xyz"), None);
+        assert!(fuzzy_match("Buy milk", "bmk").is_some());
+        assert!(fuzzy_match("BUY MILK", "milk").is_some());
+    }
+
+    #[test]
+    fn should_rank_contiguous_matches_above_scattered_ones() {
+        let contiguous = fuzzy_match("milk", "milk").unwrap();
+        let prefixed = fuzzy_match("buy milk", "milk").unwrap();
+        let scattered = fuzzy_match("make it look", "milk").unwrap();
+        assert!(contiguous > prefixed);
+        assert!(prefixed > scattered);
+    }
+
+    #[test]
+    fn should_filter_and_rank_search_results_by_score() {
+        let todos = vec![
+            titled("1", "make it look nice"),
+            titled("2", "walk the dog"),
+            titled("3", "buy milk"),
+        ];
+        assert_eq!(ids(&fuzzy_filter(&todos, "milk")), vec!["3", "1"]);
+    }
+
+    #[test]
+    fn should_keep_duplicate_titles_in_original_order() {
+        let todos = vec![
+            titled("1", "milk"),
+            titled("2", "Bread"),
+            titled("3", "Milk"),
+            titled("4", "MILK"),
+            titled("5", "bread"),
+        ];
+        assert_eq!(ids(&sort_alphabetical(&todos)), vec!["2", "5", "1", "3", "4"]);
+    }
+
+    #[test]
+    fn should_open_in_manual_order_when_sort_on_load_is_off() {
+        assert_eq!(initial_sort_mode(SortMode::Due, false), SortMode::Manual);
+        assert_eq!(initial_sort_mode(SortMode::Manual, false), SortMode::Manual);
+    }
+
+    #[test]
+    fn should_sort_on_load_when_enabled() {
+        assert_eq!(initial_sort_mode(SortMode::Due, true), SortMode::Due);
+        assert_eq!(initial_sort_mode(SortMode::Manual, true), SortMode::Manual);
+    }
+
+    #[test]
+    fn should_sort_view_without_touching_stored_order() {
+        let mut todos = sample_todos(3);
+        todos[2].due_at = Some(10.0);
+        let sorted = sort_todos(&todos, initial_sort_mode(SortMode::Due, true), 0.0);
+        assert_eq!(ids(&sorted), vec!["3", "1", "2"]);
+        assert_eq!(sorted.iter().map(|todo| todo.order).collect::<Vec<_>>(), vec![2, 0, 1]);
+    }
+
+    #[test]
+    fn should_round_trip_sort_settings() {
+        let settings = Settings {
+            sort_mode: SortMode::Due,
+            sort_on_load: true,
+            ..Default::default()
+        };
+        let encoded = serde_json::to_string(&settings).unwrap();
+        assert!(encoded.contains("\"sort_mode\":\"due\""));
+        assert_eq!(serde_json::from_str::<Settings>(&encoded).unwrap(), settings);
+        assert_eq!(
+            serde_json::from_str::<Settings>("{\"allow_placeholder\":true}").unwrap().sort_mode,
+            SortMode::Manual
+        );
+    }
+
+    #[test]
+    fn should_allow_reorder_within_same_pin_group() {
+        let mut todos = sample_todos(4);
+        todos[0].pinned = true;
+        todos[1].pinned = true;
+        assert!(validate_reorder(&todos, "1", "2"));
+        assert!(validate_reorder(&todos, "2", "1"));
+        assert!(validate_reorder(&todos, "3", "4"));
+        assert!(validate_reorder(&todos, "4", "3"));
+    }
+
+    #[test]
+    fn should_reject_reorder_across_pin_groups_or_unknown_ids() {
+        let mut todos = sample_todos(4);
+        todos[0].pinned = true;
+        todos[1].pinned = true;
+        assert!(!validate_reorder(&todos, "1", "4"));
+        assert!(!validate_reorder(&todos, "3", "2"));
+        assert!(!validate_reorder(&todos, "1", "missing"));
+        assert!(!validate_reorder(&todos, "missing", "1"));
+    }
+
+    #[test]
+    fn should_compute_date_offsets_from_local_midnight() {
+        let midnight = days_from_civil(2024, 3, 15) as f64 * ONE_DAY_MS;
+        let now = midnight + 15.0 * 60.0 * 60.0 * 1000.0;
+        assert_eq!(today_ms(now, 0.0), midnight);
+        assert_eq!(tomorrow_ms(now, 0.0), midnight + ONE_DAY_MS);
+        assert_eq!(next_week_ms(now, 0.0), midnight + 7.0 * ONE_DAY_MS);
+        let two_hours = 2.0 * 60.0 * 60.0 * 1000.0;
+        assert_eq!(today_ms(now, two_hours), midnight - two_hours);
+        assert_eq!(today_ms(midnight - 1.0, 0.0), midnight - ONE_DAY_MS);
+    }
+
+    #[test]
+    fn should_roll_overdue_active_todos_to_today() {
+        let today = 100.0 * ONE_DAY_MS;
+        let now = today + 9.0 * 60.0 * 60.0 * 1000.0;
+        let mut todos = sample_todos(5);
+        todos[0].due_at = Some(today - ONE_DAY_MS);
+        todos[1].due_at = Some(today - 3.0 * ONE_DAY_MS);
+        todos[1].completed = true;
+        todos[2].due_at = Some(today + ONE_DAY_MS);
+        todos[3].due_at = Some(today + 1_000.0);
+        let new_todos = rollover_overdue(&todos, now, 0.0);
+        assert_eq!(new_todos[0].due_at, Some(today));
+        assert!(!is_overdue(&new_todos[0], now, 0.0));
+        assert_eq!(rollover_overdue(&new_todos, now, 0.0), new_todos);
+        assert_eq!(new_todos[1], todos[1]);
+        assert_eq!(new_todos[2], todos[2]);
+        assert_eq!(new_todos[3], todos[3]);
+        assert_eq!(new_todos[4], todos[4]);
+    }
+
+    #[test]
+    fn should_rollover_once_per_day() {
+        assert!(should_rollover(None, 100));
+        assert!(should_rollover(Some(99), 100));
+        assert!(!should_rollover(Some(100), 100));
+        assert_eq!(day_number(100.0 * ONE_DAY_MS + 1.0, 0.0), 100);
+        assert_eq!(day_number(100.0 * ONE_DAY_MS - 1.0, 0.0), 99);
+        assert_eq!(day_number(100.0 * ONE_DAY_MS - 1.0, 2.0), 100);
+    }
+
+    #[test]
+    fn should_parse_date_input_values() {
+        assert_eq!(parse_date_input("1970-01-01", 0.0), Some(0.0));
+        assert_eq!(
+            parse_date_input("2024-03-15", 0.0),
+            Some(days_from_civil(2024, 3, 15) as f64 * ONE_DAY_MS)
+        );
+        assert_eq!(days_from_civil(2000, 3, 1), 11_017);
+        assert_eq!(parse_date_input("", 0.0), None);
+        assert_eq!(parse_date_input("2024-13-01", 0.0), None);
+        assert_eq!(parse_date_input("2024-03", 0.0), None);
+    }
+
+    #[test]
+    fn should_apply_due_actions() {
+        let now = 10.0 * ONE_DAY_MS + 1_000.0;
+        let mut todos = sample_todos(2);
+        todos[1].due_at = Some(now);
+        let added = apply_due_action(&todos, "1", &DueAction::AddDay, now, 0.0);
+        assert_eq!(added[0].due_at, Some(11.0 * ONE_DAY_MS));
+        let week = apply_due_action(&todos, "1", &DueAction::AddWeek, now, 0.0);
+        assert_eq!(week[0].due_at, Some(17.0 * ONE_DAY_MS));
+        let snoozed = apply_due_action(&todos, "2", &DueAction::AddDay, now, 0.0);
+        assert_eq!(snoozed[1].due_at, Some(now + ONE_DAY_MS));
+        assert_eq!(snoozed[1].snooze_count, 1);
+        let cleared = apply_due_action(&todos, "2", &DueAction::Clear, now, 0.0);
+        assert_eq!(cleared[1].due_at, None);
+        let picked = apply_due_action(&todos, "1", &DueAction::Pick("1970-01-02".to_string()), now, 0.0);
+        assert_eq!(picked[0].due_at, Some(ONE_DAY_MS));
+        let invalid = apply_due_action(&todos, "1", &DueAction::Pick(String::new()), now, 0.0);
+        assert_eq!(invalid, todos);
+    }
+
+    #[test]
+    fn should_filter_todos_by_completion() {
+        let mut todos = sample_todos(3);
+        todos[1].completed = true;
+        assert_eq!(ids(&filter_todos(&todos, Filter::All)), vec!["1", "2", "3"]);
+        assert_eq!(ids(&filter_todos(&todos, Filter::Active)), vec!["1", "3"]);
+        assert_eq!(ids(&filter_todos(&todos, Filter::Completed)), vec!["2"]);
+    }
+
+    #[test]
+    fn should_map_routes_to_filters_and_back() {
+        for filter in Filter::ALL {
+            assert_eq!(filter_for_route(&route_for_filter(filter)), filter);
+        }
+        assert_eq!(filter_for_route(&Route::NotFound), Filter::All);
+        assert_eq!(Route::recognize("/active"), Some(Route::Active));
+        assert_eq!(Route::recognize("/completed"), Some(Route::Completed));
+        assert_eq!(route_for_filter(Filter::All).to_path(), "/");
+    }
+
+    #[test]
+    fn should_recognize_edit_route() {
+        let route = Route::recognize("/edit/abc").unwrap();
+        assert_eq!(route, Route::Edit { id: "abc".to_string() });
+        assert_eq!(route.to_path(), "/edit/abc");
+        assert_eq!(filter_for_route(&route), Filter::All);
+    }
+
+    #[test]
+    fn should_resolve_edit_route_to_existing_todo() {
+        let mut todos = sample_todos(2);
+        let edit = |id: &str| Route::Edit { id: id.to_string() };
+        assert_eq!(resolve_edit_route(&todos, &edit("2")), Some(Ok("2".to_string())));
+        assert_eq!(
+            resolve_edit_route(&todos, &edit("9")),
+            Some(Err("No todo found to edit (9)".to_string()))
+        );
+        todos[1].archived = true;
+        assert!(matches!(resolve_edit_route(&todos, &edit("2")), Some(Err(_))));
+        assert_eq!(resolve_edit_route(&todos, &Route::Home), None);
+    }
+
+    #[test]
+    fn should_round_trip_filter_serialization() {
+        for filter in Filter::ALL {
+            let encoded = serde_json::to_string(&filter).unwrap();
+            assert_eq!(decode_filter(Some(&encoded)), filter);
+        }
+    }
+
+    #[test]
+    fn should_default_missing_or_invalid_filter_to_all() {
+        assert_eq!(decode_filter(None), Filter::All);
+        assert_eq!(decode_filter(Some("\"Bogus\"")), Filter::All);
+        assert_eq!(decode_filter(Some("not json")), Filter::All);
+    }
+
+    fn pointed_todos() -> Vec<Todo> {
+        let mut todos = sample_todos(4);
+        todos[0].points = Some(5);
+        todos[1].points = Some(8);
+        todos[2].points = Some(13);
+        todos[2].completed = true;
+        todos
+    }
+
+    #[test]
+    fn should_not_be_over_capacity_when_under_or_at() {
+        let todos = pointed_todos();
+        assert_eq!(active_points(&todos), 13);
+        assert!(!over_capacity(&todos, 20));
+        assert!(!over_capacity(&todos, 13));
+    }
+
+    #[test]
+    fn should_be_over_capacity_when_active_points_exceed() {
+        let todos = pointed_todos();
+        assert!(over_capacity(&todos, 12));
+        assert!(over_capacity(&todos, 0));
+        assert!(!over_capacity(&[], 0));
+    }
+
+    #[test]
+    fn should_saturate_active_points_instead_of_overflowing() {
+        let mut todos = sample_todos(2);
+        todos[0].points = Some(u32::MAX);
+        todos[1].points = Some(5);
+        assert_eq!(active_points(&todos), u32::MAX);
+        assert!(over_capacity(&todos, 20));
+    }
+
+    #[test]
+    fn should_only_retry_load_on_access_errors() {
+        assert!(should_retry_load(LoadFailure::Access));
+        assert!(!should_retry_load(LoadFailure::Missing));
+        assert!(!should_retry_load(LoadFailure::Corrupt));
+    }
+
+    #[test]
+    fn should_build_profile_storage_keys() {
+        assert_eq!(storage_key_for(DEFAULT_PROFILE), "todos:default");
+        assert_eq!(storage_key_for("kids"), "todos:kids");
+        assert_eq!(profile_from_key(&storage_key_for("kids")), Some("kids"));
+        assert_eq!(profile_from_key(LEGACY_STORAGE_KEY), None);
+        assert_eq!(profile_from_key("todos:"), None);
+        assert_eq!(profile_from_key("settings"), None);
+    }
+
+    #[test]
+    fn should_list_profiles_from_storage_keys() {
+        let keys = ["todos:work", "settings", "todos", "todos:home", "todos:work"]
+            .map(str::to_string);
+        assert_eq!(profiles_from_keys(keys), vec!["default", "home", "work"]);
+        assert_eq!(profiles_from_keys(Vec::new()), vec!["default"]);
+    }
+
+    #[test]
+    fn should_normalize_profile_names() {
+        assert_eq!(normalize_profile("  kids "), Some("kids".to_string()));
+        assert_eq!(normalize_profile("   "), None);
+    }
+
+    #[test]
+    fn should_classify_load_errors() {
+        let missing = StorageError::KeyNotFound(storage_key_for(DEFAULT_PROFILE));
+        assert_eq!(classify_load_error(&missing), LoadFailure::Missing);
+        let corrupt = StorageError::SerdeError(serde_json::from_str::<Vec<Todo>>("{").unwrap_err());
+        assert_eq!(classify_load_error(&corrupt), LoadFailure::Corrupt);
+    }
+
+    #[test]
+    fn should_archive_todo_by_id() {
+        let todos = vec![
+            Todo {
+                id: "1".to_string(),
+                title: "Task 1".to_string(),
+                completed: false,
+                ..Default::default()
+            },
+            Todo {
+                id: "2".to_string(),
+                title: "Task 2".to_string(),
+                completed: true,
+                ..Default::default()
+            },
+        ];
+        let new_todos = archive_todo(&todos, "1");
+        assert_eq!(new_todos.len(), 2);
+        assert!(new_todos[0].archived);
+        assert_eq!(new_todos[0].title, "Task 1");
+        assert!(!new_todos[0].completed);
+        assert!(!new_todos[1].archived);
+    }
+
+    #[test]
+    fn should_restore_archived_todo_by_id() {
+        let todos = vec![
+            Todo {
+                id: "1".to_string(),
+                title: "Task 1".to_string(),
+                completed: false,
+                archived: true,
+                ..Default::default()
+            },
+            Todo {
+                id: "2".to_string(),
+                title: "Task 2".to_string(),
+                completed: true,
+                archived: true,
+                ..Default::default()
+            },
+        ];
+        let new_todos = restore_todo(&todos, "1");
+        assert_eq!(new_todos.len(), 2);
+        assert!(!new_todos[0].archived);
+        assert_eq!(new_todos[0].title, "Task 1");
+        assert!(new_todos[1].archived);
+    }
+
+    #[test]
+    fn should_list_todos_due_within_window() {
+        let mut todos = sample_todos(5);
+        todos[0].due_at = Some(1_000.0 + 5.0 * ONE_MINUTE_MS);
+        todos[1].due_at = Some(1_000.0 + REMINDER_WINDOW_MS);
+        todos[2].due_at = Some(1_000.0 + REMINDER_WINDOW_MS + 1.0);
+        todos[3].due_at = Some(999.0);
+        assert_eq!(due_soon(&todos, 1_000.0, REMINDER_WINDOW_MS), vec!["1", "2"]);
+    }
+
+    #[test]
+    fn should_skip_completed_and_archived_todos_when_due_soon() {
+        let mut todos = sample_todos(3);
+        for todo in &mut todos {
+            todo.due_at = Some(1_000.0);
+        }
+        todos[0].completed = true;
+        todos[1].archived = true;
+        assert_eq!(due_soon(&todos, 1_000.0, REMINDER_WINDOW_MS), vec!["3"]);
+        assert!(due_soon(&[], 1_000.0, REMINDER_WINDOW_MS).is_empty());
+    }
+
+    #[test]
+    fn should_push_due_date_forward_when_snoozing() {
+        let mut todos = sample_todos(1);
+        todos[0].due_at = Some(1_000.0);
+        let snoozed = snooze_todo(&todos, "1", ONE_DAY_MS);
+        assert_eq!(snoozed[0].due_at, Some(1_000.0 + ONE_DAY_MS));
+    }
+
+    #[test]
+    fn should_not_snooze_undated_todo() {
+        let todos = sample_todos(2);
+        assert_eq!(snooze_todo(&todos, "1", ONE_DAY_MS), todos);
+    }
+
+    #[test]
+    fn should_leave_other_todos_untouched_when_snoozing() {
+        let mut todos = sample_todos(3);
+        todos[0].due_at = Some(1_000.0);
+        todos[1].due_at = Some(2_000.0);
+        let snoozed = snooze_todo(&todos, "2", ONE_DAY_MS);
+        assert_eq!(snoozed[0], todos[0]);
+        assert_eq!(snoozed[2], todos[2]);
+        assert_eq!(snoozed[1].due_at, Some(2_000.0 + ONE_DAY_MS));
+    }
+
+    #[test]
+    fn should_increment_snooze_count_on_repeated_snoozes() {
+        let todos = vec![
+            Todo {
+                id: "1".to_string(),
+                title: "Task 1".to_string(),
+                completed: false,
+                due_at: Some(1_000.0),
+                ..Default::default()
+            },
+            Todo {
+                id: "2".to_string(),
+                title: "Task 2".to_string(),
+                completed: true,
+                due_at: Some(2_000.0),
+                ..Default::default()
+            },
+        ];
+        let once = snooze_todo(&todos, "1", ONE_DAY_MS);
+        let twice = snooze_todo(&once, "1", ONE_DAY_MS);
+        let thrice = snooze_todo(&twice, "1", ONE_DAY_MS);
+        assert_eq!(once[0].snooze_count, 1);
+        assert_eq!(twice[0].snooze_count, 2);
+        assert_eq!(thrice[0].snooze_count, 3);
+        assert_eq!(thrice[0].id, "1");
+        assert_eq!(thrice[0].title, "Task 1");
+        assert!(!thrice[0].completed);
+        assert_eq!(thrice[1].snooze_count, 0);
+        assert_eq!(thrice[1].due_at, Some(2_000.0));
+    }
+
+    #[test]
+    fn should_restore_exact_prior_list_from_snapshot() {
+        let todos = vec![
+            Todo {
+                id: "1".to_string(),
+                title: "Task 1".to_string(),
+                completed: false,
+                ..Default::default()
+            },
+            Todo {
+                id: "2".to_string(),
+                title: "Task 2".to_string(),
+                completed: true,
+                ..Default::default()
+            },
+        ];
+        let (new_todos, snapshot) = with_snapshot(&todos, |todos| delete_todo(todos, "1"));
+        assert_eq!(new_todos.len(), 1);
+        assert_eq!(snapshot, todos);
+    }
+
+    fn titled(id: &str, title: &str) -> Todo {
+        Todo {
+            id: id.to_string(),
+            title: title.to_string(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn should_quote_csv_titles_containing_commas() {
+        assert_eq!(
+            export_todos_csv(&[titled("1", "eggs, milk, bread")]),
+            "id,title,completed\r\n1,\"eggs, milk, bread\",false\r\n"
+        );
+    }
+
+    #[test]
+    fn should_double_quotes_inside_csv_titles() {
+        assert_eq!(
+            export_todos_csv(&[titled("1", "read \"Dune\"")]),
+            "id,title,completed\r\n1,\"read \"\"Dune\"\"\",false\r\n"
+        );
+    }
+
+    #[test]
+    fn should_quote_csv_titles_containing_newlines() {
+        assert_eq!(
+            export_todos_csv(&[titled("1", "line one\nline two"), titled("2", "a\r\nb")]),
+            "id,title,completed\r\n1,\"line one\nline two\",false\r\n2,\"a\r\nb\",false\r\n"
+        );
+    }
+
+    #[test]
+    fn should_export_header_only_for_empty_list() {
+        assert_eq!(export_todos_csv(&[]), "id,title,completed\r\n");
+    }
+
+    #[test]
+    fn should_parse_well_formed_csv() {
+        let todos = parse_todos_csv("id,title,completed\r\n1,Buy milk,false\r\n2,Walk dog,true\r\n").unwrap();
+        assert_eq!(todos.len(), 2);
+        assert_eq!((todos[0].id.as_str(), todos[0].title.as_str(), todos[0].completed), ("1", "Buy milk", false));
+        assert_eq!((todos[1].id.as_str(), todos[1].title.as_str(), todos[1].completed), ("2", "Walk dog", true));
+    }
+
+    #[test]
+    fn should_parse_quoted_csv_fields() {
+        let csv = "id,title,completed\n1,\"eggs, milk\",false\n2,\"read \"\"Dune\"\"\",true\n3,\"line one\nline two\",false";
+        let todos = parse_todos_csv(csv).unwrap();
+        assert_eq!(todos[0].title, "eggs, milk");
+        assert_eq!(todos[1].title, "read \"Dune\"");
+        assert_eq!(todos[2].title, "line one\nline two");
+    }
+
+    #[test]
+    fn should_round_trip_exported_csv() {
+        let todos = vec![titled("1", "a, \"b\"\nc"), titled("2", "plain")];
+        assert_eq!(parse_todos_csv(&export_todos_csv(&todos)).unwrap(), todos);
+    }
+
+    #[test]
+    fn should_assign_fresh_ids_without_id_column() {
+        let todos = parse_todos_csv("title\nFirst\nSecond\n").unwrap();
+        assert_eq!(todos.len(), 2);
+        assert!(!todos[0].id.is_empty());
+        assert_ne!(todos[0].id, todos[1].id);
+        assert!(!todos[0].completed);
+    }
+
+    #[test]
+    fn should_reject_malformed_csv() {
+        assert_eq!(parse_todos_csv(""), Err("missing header row".to_string()));
+        assert_eq!(parse_todos_csv("id,name\n1,x"), Err("missing title column".to_string()));
+        assert_eq!(
+            parse_todos_csv("id,title,completed\n1,Task"),
+            Err("row 2 has 2 fields, expected 3".to_string())
+        );
+        assert_eq!(
+            parse_todos_csv("id,title,completed\n1,\"Task,false"),
+            Err("unterminated quoted field".to_string())
+        );
+        assert_eq!(
+            parse_todos_csv("id,title,completed\n1,Task,maybe"),
+            Err("row 2 has invalid completed value \"maybe\"".to_string())
+        );
+    }
+
+    #[test]
+    fn should_dispatch_import_on_file_extension() {
+        assert_eq!(parse_import("todos.CSV", "title\nTask").unwrap()[0].title, "Task");
+        assert_eq!(
+            parse_import("todos.json", r#"[{"id":"1","title":"Task","completed":false}]"#).unwrap(),
+            vec![titled("1", "Task")]
+        );
+        assert!(parse_import("todos.json", "title\nTask").is_err());
+    }
+
+    #[test]
+    fn should_route_export_to_matching_serializer() {
+        let todos = vec![
+            Todo {
+                id: "1".to_string(),
+                title: "Task 1".to_string(),
+                completed: false,
+                ..Default::default()
+            },
+            Todo {
+                id: "2".to_string(),
+                title: "Task, 2".to_string(),
+                completed: true,
+                ..Default::default()
+            },
+        ];
+        assert_eq!(render_export(&todos, ExportFormat::Json), export_todos_json(&todos));
+        assert_eq!(render_export(&todos, ExportFormat::Csv), export_todos_csv(&todos));
+        assert_eq!(
+            render_export(&todos, ExportFormat::Markdown),
+            export_todos_markdown(&todos)
+        );
+        assert_eq!(
+            render_export(&todos, ExportFormat::Csv),
+            "id,title,completed\r\n1,Task 1,false\r\n2,\"Task, 2\",true\r\n"
+        );
+        assert_eq!(
+            render_export(&todos, ExportFormat::Markdown),
+            "- [ ] Task 1\n- [x] Task, 2\n"
+        );
+    }
+
+    fn sample_todos(count: usize) -> Vec<Todo> {
+        (1..=count)
+            .map(|i| Todo {
+                id: i.to_string(),
+                title: format!("Task {}", i),
+                order: i as u32 - 1,
+                ..Default::default()
+            })
+            .collect()
+    }
+
+    fn ids(todos: &[Todo]) -> Vec<&str> {
+        todos.iter().map(|todo| todo.id.as_str()).collect()
+    }
+
+    #[test]
+    fn should_compute_flip_deltas_for_moved_rows_only() {
+        let before: HashMap<String, f64> =
+            [("1", 0.0), ("2", 40.0), ("3", 80.0)].map(|(id, top)| (id.to_string(), top)).into();
+        let after: HashMap<String, f64> =
+            [("1", 40.0), ("2", 0.0), ("3", 80.0), ("4", 120.0)].map(|(id, top)| (id.to_string(), top)).into();
+        assert_eq!(
+            flip_deltas(&before, &after),
+            vec![("1".to_string(), -40.0), ("2".to_string(), 40.0)]
+        );
+    }
+
+    #[test]
+    fn should_skip_animation_when_disabled_or_reduced_motion() {
+        assert!(should_animate(true, false));
+        assert!(!should_animate(true, true));
+        assert!(!should_animate(false, false));
+    }
+
+    #[test]
+    fn should_collapse_duplicate_titles_keeping_first_occurrence() {
+        let todos = vec![
+            titled("1", "Buy milk"),
+            titled("2", "Walk dog"),
+            titled("3", "  buy MILK "),
+            titled("4", "Call mom"),
+            titled("5", "walk dog"),
+        ];
+        assert_eq!(ids(&dedupe_todos(&todos)), vec!["1", "2", "4"]);
+        assert_eq!(dedupe_todos(&sample_todos(3)), sample_todos(3));
+    }
+
+    #[test]
+    fn should_or_completed_flags_when_deduping() {
+        let mut todos = vec![
+            titled("1", "Buy milk"),
+            titled("2", "buy milk"),
+            titled("3", "Walk dog"),
+            titled("4", "Walk dog"),
+        ];
+        todos[1].completed = true;
+        todos[1].completed_at = Some(5.0);
+        todos[2].completed = true;
+        let deduped = dedupe_todos(&todos);
+        assert_eq!(ids(&deduped), vec!["1", "3"]);
+        assert!(deduped[0].completed);
+        assert_eq!(deduped[0].completed_at, Some(5.0));
+        assert!(deduped[1].completed);
+    }
+
+    #[test]
+    fn should_leave_archived_todos_out_of_dedupe() {
+        let mut todos = vec![
+            titled("1", "Buy milk"),
+            titled("2", "buy milk"),
+            titled("3", "Buy milk"),
+        ];
+        todos[0].archived = true;
+        todos[2].archived = true;
+        assert_eq!(ids(&dedupe_todos(&todos)), vec!["1", "2", "3"]);
+    }
+
+    #[test]
+    fn should_not_merge_completion_into_todo_awaiting_review() {
+        let mut todos = vec![titled("1", "Buy milk"), titled("2", "buy milk")];
+        todos[0].require_review = true;
+        todos[1].completed = true;
+        todos[1].completed_at = Some(5.0);
+        let deduped = dedupe_todos(&todos);
+        assert_eq!(ids(&deduped), vec!["1"]);
+        assert!(!deduped[0].completed);
+        assert_eq!(deduped[0].completed_at, None);
+    }
+
+    fn visible(ids: &[&str]) -> Vec<String> {
+        ids.iter().map(|id| id.to_string()).collect()
+    }
+
+    #[test]
+    fn should_move_todo_up() {
+        let todos = sample_todos(3);
+        let shown = visible(&["1", "2", "3"]);
+        assert_eq!(ids(&move_todo_up(&todos, "2", &shown)), vec!["2", "1", "3"]);
+        assert_eq!(ids(&move_todo_up(&todos, "3", &shown)), vec!["1", "3", "2"]);
+    }
+
+    #[test]
+    fn should_move_todo_down() {
+        let todos = sample_todos(3);
+        let shown = visible(&["1", "2", "3"]);
+        assert_eq!(ids(&move_todo_down(&todos, "1", &shown)), vec!["2", "1", "3"]);
+        assert_eq!(ids(&move_todo_down(&todos, "2", &shown)), vec!["1", "3", "2"]);
+    }
+
+    #[test]
+    fn should_not_move_first_todo_up_or_last_todo_down() {
+        let todos = sample_todos(3);
+        let shown = visible(&["1", "2", "3"]);
+        assert_eq!(move_todo_up(&todos, "1", &shown), todos);
+        assert_eq!(move_todo_down(&todos, "3", &shown), todos);
+        assert_eq!(move_todo_up(&todos, "missing", &shown), todos);
+    }
+
+    #[test]
+    fn should_swap_with_nearest_visible_neighbour() {
+        let todos = sample_todos(4);
+        let shown = visible(&["1", "3", "4"]);
+        assert_eq!(ids(&move_todo_up(&todos, "3", &shown)), vec!["3", "2", "1", "4"]);
+        assert_eq!(ids(&move_todo_down(&todos, "1", &shown)), vec!["3", "2", "1", "4"]);
+        assert_eq!(move_todo_up(&todos, "1", &shown), todos);
+        assert_eq!(move_todo_down(&todos, "2", &shown), todos);
+    }
+
+    #[test]
+    fn should_list_rendered_rows_in_group_order() {
+        let mut todos = sample_todos(4);
+        todos[0].tags = vec!["work".to_string()];
+        todos[2].tags = vec!["home".to_string()];
+        todos[3].tags = vec!["work".to_string()];
+        let layout = |grouped: bool, expanded_group: Option<&'static str>| RowLayout {
+            due_grouped: false,
+            grouped,
+            expanded_group,
+        };
+        let rows = |layout| rendered_row_ids(&todos, &todos[..2], layout, 0.0, 0.0);
+        assert_eq!(rows(layout(false, None)), vec!["1", "2"]);
+        assert_eq!(rows(layout(true, Some("#work"))), vec!["1", "4"]);
+        assert!(rows(layout(true, None)).is_empty());
+    }
+
+    #[test]
+    fn should_keep_pinned_block_when_moving_with_arrows() {
+        let mut todos = sample_todos(3);
+        todos[0].pinned = true;
+        let shown = visible(&["1", "2", "3"]);
+        let action = |id: &str, up: bool| {
+            let (id, visible) = (id.to_string(), shown.clone());
+            if up {
+                TodoAction::MoveUp { id, visible }
+            } else {
+                TodoAction::MoveDown { id, visible }
+            }
+        };
+        assert_eq!(reduce(&todos, action("2", true)), Ok(todos.clone()));
+        assert_eq!(reduce(&todos, action("1", false)), Ok(todos.clone()));
+        assert_eq!(ids(&reduce(&todos, action("2", false)).unwrap()), vec!["1", "3", "2"]);
+    }
+
+    #[test]
+    fn should_keep_pinned_block_when_moving_selection_to_edge() {
+        let mut todos = sample_todos(4);
+        todos[0].pinned = true;
+        todos[1].pinned = true;
+        let selected = HashSet::from(["2".to_string(), "4".to_string()]);
+        let to_top = reduce(
+            &todos,
+            TodoAction::MoveToEdge {
+                ids: selected.clone(),
+                to_top: true,
+            },
+        )
+        .unwrap();
+        assert_eq!(ids(&to_top), vec!["2", "1", "4", "3"]);
+        assert!(to_top[0].pinned && to_top[1].pinned && !to_top[2].pinned);
+        let to_bottom = reduce(&todos, TodoAction::MoveToEdge { ids: selected, to_top: false }).unwrap();
+        assert_eq!(ids(&to_bottom), vec!["1", "2", "3", "4"]);
+    }
+
+    #[test]
+    fn should_not_reorder_across_pinned_block() {
+        let mut todos = sample_todos(3);
+        todos[0].pinned = true;
+        assert_eq!(reduce(&todos, TodoAction::Reorder { from: 2, to: 0 }), Ok(todos.clone()));
+        assert_eq!(ids(&reduce(&todos, TodoAction::Reorder { from: 2, to: 1 }).unwrap()), vec!["1", "3", "2"]);
+    }
+
+    #[test]
+    fn should_move_relative_to_order_not_storage_position() {
+        let mut todos = sample_todos(3);
+        todos.reverse();
+        let shown = visible(&["1", "2", "3"]);
+        assert_eq!(ids(&move_todo_up(&todos, "2", &shown)), vec!["2", "1", "3"]);
+        assert_eq!(ids(&move_todo_down(&todos, "2", &shown)), vec!["1", "3", "2"]);
+        assert_eq!(ids(&reorder(&todos, 0, 2)), vec!["2", "3", "1"]);
+        let selected = HashSet::from(["2".to_string()]);
+        assert_eq!(ids(&move_selection_to_edge(&todos, &selected, true)), vec!["2", "1", "3"]);
+        let moved = move_todo_up(&todos, "3", &shown);
+        assert_eq!(moved.iter().map(|todo| todo.order).collect::<Vec<_>>(), vec![0, 1, 2]);
+    }
+
+    #[test]
+    fn should_escalate_priority_within_a_day_of_due() {
+        let now = 1_000_000.0;
+        let todo = Todo {
+            priority: Priority::Low,
+            due_at: Some(now + ONE_DAY_MS),
+            ..Default::default()
+        };
+        assert_eq!(effective_priority(&todo, now), Priority::High);
+        assert_eq!(todo.priority, Priority::Low);
+        let overdue = Todo {
+            due_at: Some(now - 1.0),
+            ..todo.clone()
+        };
+        assert_eq!(effective_priority(&overdue, now), Priority::High);
+    }
+
+    #[test]
+    fn should_escalate_low_priority_to_medium_within_three_days() {
+        let now = 1_000_000.0;
+        let todo = Todo {
+            priority: Priority::Low,
+            due_at: Some(now + 2.0 * ONE_DAY_MS),
+            ..Default::default()
+        };
+        assert_eq!(effective_priority(&todo, now), Priority::Medium);
+    }
+
+    #[test]
+    fn should_not_escalate_when_far_from_due_or_undated() {
+        let now = 1_000_000.0;
+        let far = Todo {
+            priority: Priority::Low,
+            due_at: Some(now + 3.0 * ONE_DAY_MS + 1.0),
+            ..Default::default()
+        };
+        assert_eq!(effective_priority(&far, now), Priority::Low);
+        let undated = Todo {
+            priority: Priority::Low,
+            ..Default::default()
+        };
+        assert_eq!(effective_priority(&undated, now), Priority::Low);
+        let high = Todo {
+            priority: Priority::High,
+            due_at: Some(now + 10.0 * ONE_DAY_MS),
+            ..Default::default()
+        };
+        assert_eq!(effective_priority(&high, now), Priority::High);
+    }
+
+    #[test]
+    fn should_sort_by_effective_priority() {
+        let now = 1_000_000.0;
+        let mut todos = sample_todos(3);
+        todos[0].priority = Priority::Low;
+        todos[1].priority = Priority::Medium;
+        todos[2].priority = Priority::Low;
+        todos[2].due_at = Some(now + 1.0);
+        assert_eq!(ids(&sort_todos(&todos, SortMode::Priority, now)), vec!["3", "2", "1"]);
+        assert_eq!(ids(&sort_todos(&todos, SortMode::Manual, now)), vec!["1", "2", "3"]);
+    }
+
+    #[test]
+    fn should_renumber_order_after_reorder() {
+        let reordered = reorder(&sample_todos(4), 3, 0);
+        assert_eq!(ids(&reordered), vec!["4", "1", "2", "3"]);
+        assert_eq!(reordered.iter().map(|todo| todo.order).collect::<Vec<_>>(), vec![0, 1, 2, 3]);
+        let mut shuffled = reordered.clone();
+        shuffled.reverse();
+        assert_eq!(sort_by_order(&shuffled), reordered);
+    }
+
+    #[test]
+    fn should_order_new_todo_after_existing_ones() {
+        let mut todos = sample_todos(3);
+        todos[1].order = 7;
+        let new_todos = create_new_todo(&todos, "Task 4".to_string(), false, 0.0);
+        assert_eq!(new_todos[3].order, 8);
+        assert_eq!(create_new_todo(&[], "First".to_string(), false, 0.0)[0].order, 0);
+    }
+
+    #[test]
+    fn should_assign_sequential_order_to_legacy_todos() {
+        let legacy: Vec<Todo> =
+            serde_json::from_str(r#"[{"id":"1","title":"A","completed":false},{"id":"2","title":"B","completed":false}]"#)
+                .unwrap();
+        let migrated = migrate_order(legacy);
+        assert_eq!(migrated.iter().map(|todo| todo.order).collect::<Vec<_>>(), vec![0, 1]);
+        let mut ordered = sample_todos(3);
+        ordered[0].order = 5;
+        assert_eq!(migrate_order(ordered.clone()), ordered);
+    }
+
+    #[test]
+    fn should_reorder_todo_forward_and_backward() {
+        let todos = sample_todos(4);
+        assert_eq!(ids(&reorder(&todos, 0, 2)), vec!["2", "3", "1", "4"]);
+        assert_eq!(ids(&reorder(&todos, 3, 0)), vec!["4", "1", "2", "3"]);
+        assert_eq!(ids(&reorder(&todos, 1, 3)), vec!["1", "3", "4", "2"]);
+        assert_eq!(ids(&reorder(&todos, 2, 1)), vec!["1", "3", "2", "4"]);
+    }
+
+    #[test]
+    fn should_not_reorder_when_indices_are_equal_or_out_of_range() {
+        let todos = sample_todos(3);
+        assert_eq!(reorder(&todos, 1, 1), todos);
+        assert_eq!(reorder(&todos, 3, 0), todos);
+        assert_eq!(reorder(&todos, 0, 3), todos);
+        assert!(reorder(&[], 0, 0).is_empty());
+    }
+
+    #[test]
+    fn should_detect_snapshot_differences() {
+        let todos = sample_todos(2);
+        assert!(!differs(&todos, &todos.clone()));
+        assert!(differs(&todos, &toggle_todo(&todos, "1", 0.0)));
+        assert!(differs(&todos, &delete_todo(&todos, "2")));
+        assert!(!differs(&[], &[]));
+    }
+
+    #[test]
+    fn should_round_trip_json_import() {
+        let todos = sample_todos(2);
+        assert_eq!(parse_todos_json(&export_todos_json(&todos)), Ok(todos));
+        assert!(parse_todos_json("not json").is_err());
+    }
+
+    #[test]
+    fn should_duplicate_todo_after_original() {
+        let mut todos = sample_todos(3);
+        todos[1].completed = true;
+        todos[1].completed_at = Some(3.0);
+        todos[1].review_confirmed = true;
+        todos[1].title_history = vec![(1.0, "Old".to_string())];
+        todos[1].created_at = Some(1.0);
+        todos[1].updated_at = Some(3.0);
+        let new_todos = duplicate_todo(&todos, "2", 9.0);
+        assert_eq!(new_todos.len(), 4);
+        assert_eq!(new_todos[1].id, "2");
+        assert_ne!(new_todos[2].id, "2");
+        assert_eq!(new_todos[2].title, "Task 2 (copy)");
+        assert!(!new_todos[2].completed);
+        assert_eq!(new_todos[2].completed_at, None);
+        assert!(!new_todos[2].review_confirmed);
+        assert!(new_todos[2].title_history.is_empty());
+        assert_eq!(new_todos[2].created_at, Some(9.0));
+        assert_eq!(new_todos[2].updated_at, None);
+        assert_eq!(new_todos[3].id, "3");
+    }
+
+    #[test]
+    fn should_not_duplicate_missing_todo() {
+        let todos = sample_todos(2);
+        assert_eq!(duplicate_todo(&todos, "missing", 0.0), todos);
+    }
+
+    #[test]
+    fn should_resolve_submit_title_when_rejecting_empty() {
+        assert_eq!(resolve_submit_title("  Task  ", false), Some("Task".to_string()));
+        assert_eq!(resolve_submit_title("", false), None);
+        assert_eq!(resolve_submit_title("   ", false), None);
+    }
+
+    #[test]
+    fn should_resolve_submit_title_to_placeholder_when_allowed() {
+        assert_eq!(resolve_submit_title("  Task  ", true), Some("Task".to_string()));
+        assert_eq!(resolve_submit_title("", true), Some(PLACEHOLDER_TITLE.to_string()));
+        assert_eq!(resolve_submit_title("   ", true), Some(PLACEHOLDER_TITLE.to_string()));
+    }
+
+    #[test]
+    fn should_delete_many_by_ids() {
+        let todos = sample_todos(4);
+        let ids_to_delete: HashSet<String> = ["1", "3"].iter().map(|id| id.to_string()).collect();
+        assert_eq!(ids(&delete_many(&todos, &ids_to_delete)), vec!["2", "4"]);
+    }
+
+    #[test]
+    fn should_not_delete_any_with_empty_or_unknown_ids() {
+        let todos = sample_todos(3);
+        assert_eq!(delete_many(&todos, &HashSet::new()), todos);
+        let unknown: HashSet<String> = ["7", "8"].iter().map(|id| id.to_string()).collect();
+        assert_eq!(delete_many(&todos, &unknown), todos);
+    }
+
+    #[test]
+    fn should_clear_tags_only_for_selected_todos() {
+        let mut todos = sample_todos(3);
+        for todo in todos.iter_mut() {
+            todo.tags = vec!["work".to_string(), "home".to_string()];
+        }
+        let selected: HashSet<String> = ["1", "3"].iter().map(|id| id.to_string()).collect();
+        let new_todos = clear_tags_many(&todos, &selected);
+        assert!(new_todos[0].tags.is_empty());
+        assert_eq!(new_todos[1].tags, vec!["work", "home"]);
+        assert!(new_todos[2].tags.is_empty());
+        assert_eq!(new_todos[0].title, "Task 1");
+    }
+
+    #[test]
+    fn should_extract_hash_tags_from_title() {
+        assert_eq!(
+            extract_tags("Buy milk #errands #home"),
+            ("Buy milk".to_string(), vec!["errands".to_string(), "home".to_string()])
+        );
+        assert_eq!(
+            extract_tags("Fix #1 bug #work #work"),
+            ("Fix #1 bug".to_string(), vec!["work".to_string()])
+        );
+        assert_eq!(
+            extract_tags("Ship #v2_beta #42a"),
+            ("Ship #42a".to_string(), vec!["v2_beta".to_string()])
+        );
+        assert_eq!(extract_tags("No tags # here"), ("No tags # here".to_string(), vec![]));
+        assert_eq!(extract_tags("#only"), ("#only".to_string(), vec![]));
+    }
+
+    #[test]
+    fn should_resolve_last_touched_todo_for_toggle_shortcut() {
+        let mut todos = sample_todos(3);
+        assert_eq!(resolve_toggle_target(&todos, Some("2")), Some("2".to_string()));
+        assert_eq!(resolve_toggle_target(&todos, None), None);
+        assert_eq!(resolve_toggle_target(&todos, Some("missing")), None);
+        todos[1].archived = true;
+        assert_eq!(resolve_toggle_target(&todos, Some("2")), None);
+    }
+
+    #[test]
+    fn should_toggle_resolved_target_with_toggle_todo() {
+        let todos = sample_todos(2);
+        let id = resolve_toggle_target(&todos, Some("1")).unwrap();
+        let toggled = toggle_todo(&todos, &id, 5.0);
+        assert!(toggled[0].completed);
+        assert_eq!(toggled[1], todos[1]);
+    }
+
+    #[test]
+    fn should_toggle_selection_membership() {
+        let selected = toggle_selected(&HashSet::new(), "1");
+        assert!(selected.contains("1"));
+        let selected = toggle_selected(&selected, "2");
+        assert_eq!(selected.len(), 2);
+        let selected = toggle_selected(&selected, "1");
+        assert!(!selected.contains("1"));
+        assert!(selected.contains("2"));
+    }
+
+    #[test]
+    fn should_compute_select_all_tri_state() {
+        let visible: HashSet<String> = ["1", "2"].map(str::to_string).into();
+        assert_eq!(select_all_state(&HashSet::new(), &visible), Some(false));
+        let some: HashSet<String> = ["1", "9"].map(str::to_string).into();
+        assert_eq!(select_all_state(&some, &visible), None);
+        let all: HashSet<String> = ["1", "2", "9"].map(str::to_string).into();
+        assert_eq!(select_all_state(&all, &visible), Some(true));
+        assert_eq!(select_all_state(&all, &HashSet::new()), Some(false));
+    }
+
+    #[test]
+    fn should_select_and_clear_only_visible_ids() {
+        let visible: HashSet<String> = ["1", "2"].map(str::to_string).into();
+        let hidden: HashSet<String> = ["9".to_string()].into();
+        let selected = set_all_selected(&hidden, &visible, true);
+        assert_eq!(selected.len(), 3);
+        assert_eq!(set_all_selected(&selected, &visible, false), hidden);
+    }
+
+    #[test]
+    fn should_set_completed_at_when_toggling_on() {
+        let todos = sample_todos(2);
+        let new_todos = toggle_todo(&todos, "1", 1_234.0);
+        assert!(new_todos[0].completed);
+        assert_eq!(new_todos[0].completed_at, Some(1_234.0));
+        assert_eq!(new_todos[1], todos[1]);
+    }
+
+    #[test]
+    fn should_clear_completed_at_when_toggling_off() {
+        let todos = toggle_todo(&sample_todos(2), "1", 1_234.0);
+        let new_todos = toggle_todo(&todos, "1", 5_678.0);
+        assert!(!new_todos[0].completed);
+        assert_eq!(new_todos[0].completed_at, None);
+        assert_eq!(new_todos[1], todos[1]);
+    }
+
+    #[test]
+    fn should_use_checkmark_instead_of_strikethrough_when_printing() {
+        let done = item_classes(true, true);
+        assert_eq!(done.marker, "✓");
+        assert!(!done.title.contains("line-through"));
+        assert_eq!(item_classes(false, true).marker, "☐");
+    }
+
+    #[test]
+    fn should_keep_screen_classes_outside_print_view() {
+        assert!(item_classes(true, false).title.contains("line-through"));
+        assert_eq!(item_classes(false, false).marker, "");
+        assert_ne!(item_classes(false, false).row, item_classes(false, true).row);
+    }
 
-fn save_todos_to_storage_with_error(
-    key: &str,
-    todos: &[Todo],
-    error_handle: &UseStateHandle<Option<String>>,
-) {
-    if let Err(e) = LocalStorage::set(key, todos) {
-        error_handle.set(Some(format!("Storage error: {:?}", e)));
-    } else {
-        error_handle.set(None);
+    #[test]
+    fn should_render_print_view_without_inputs() {
+        let mut todos = sample_todos(2);
+        todos[0].completed = true;
+        let mut text = String::new();
+        assert!(collect_text(&render_print_view(&todos, Callback::noop()), &mut text));
+        assert_eq!(text, format!("{}Done✓Task 1☐Task 2", APP_TITLE));
     }
-}
 
-fn update_todos_state(todos_handle: &UseStateHandle<Vec<Todo>>, new_todos: Vec<Todo>) {
-    todos_handle.set(new_todos);
-}
+    #[test]
+    fn should_render_placeholder_title_as_plain_text() {
+        let mut text = String::new();
+        assert!(collect_text(&render_title("  ", false, Callback::noop()), &mut text));
+        assert_eq!(text, "(no title)");
+    }
 
-fn update_todos(
-    todos_handle: &UseStateHandle<Vec<Todo>>,
-    new_todos: Vec<Todo>,
-    error_handle: &UseStateHandle<Option<String>>,
-) {
-    save_todos_to_storage_with_error(STORAGE_KEY, &new_todos, error_handle);
-    update_todos_state(todos_handle, new_todos);
-}
+    #[test]
+    fn should_render_bold_and_links_as_inline_markdown() {
+        assert_eq!(
+            render_markdown_inline("Read **this** [doc](https://example.com/a?b=1&c=2)"),
+            "Read <strong>this</strong> <a href=\"https://example.com/a?b=1&amp;c=2\" target=\"_blank\" rel=\"noopener noreferrer\" class=\"text-blue-600 underline\">doc</a>"
+        );
+        assert_eq!(render_markdown_inline("*em* and `code`"), "<em>em</em> and <code>code</code>");
+    }
 
-fn clear_input(input: &HtmlInputElement) {
-    input.set_value("");
-}
+    #[test]
+    fn should_escape_raw_html_in_markdown_titles() {
+        assert_eq!(
+            render_markdown_inline("<script>alert('x')</script> <b>bold</b>"),
+            "&lt;script&gt;alert(&#39;x&#39;)&lt;/script&gt; &lt;b&gt;bold&lt;/b&gt;"
+        );
+        assert_eq!(render_markdown_inline("[click](javascript:alert(1))"), "click");
+    }
 
-fn delete_todo(todos: &[Todo], id: &str) -> Vec<Todo> {
-    todos.iter().filter(|todo| todo.id != id).cloned().collect()
-}
+    #[test]
+    fn should_flatten_block_markdown_to_inline() {
+        assert_eq!(render_markdown_inline("# Heading"), "Heading");
+        assert_eq!(render_markdown_inline("- one\n- two"), "one two");
+        assert_eq!(render_markdown_inline("> quoted"), "quoted");
+    }
 
-fn toggle_todo(todos: &[Todo], id: &str) -> Vec<Todo> {
-    todos
-        .iter()
-        .map(|todo| {
-            if todo.id == id {
-                Todo {
-                    completed: !todo.completed,
-                    ..todo.clone()
-                }
-            } else {
-                todo.clone()
-            }
-        })
-        .collect()
-}
+    #[test]
+    fn should_keep_failed_payload_until_retry_succeeds() {
+        let todos = sample_todos(2);
+        let mut pending = PendingWrite::default();
+        assert!(pending
+            .attempt("todos", &todos, |_, _| Err("quota"))
+            .is_err());
+        assert!(pending.is_pending());
+        assert_eq!(pending.pending_key(), Some("todos"));
+        assert_eq!(pending.retry(|_, _| Err("quota")), Err("quota"));
+        assert!(pending.is_pending());
+        let mut saved = Vec::new();
+        assert_eq!(
+            pending.retry(|key, todos| {
+                saved.push((key.to_string(), todos.to_vec()));
+                Ok::<(), &str>(())
+            }),
+            Ok(())
+        );
+        assert!(!pending.is_pending());
+        assert_eq!(pending.pending_key(), None);
+        assert_eq!(saved, vec![("todos".to_string(), todos)]);
+    }
 
-fn update_todo_title(todos: &[Todo], id: &str, title: &str) -> Vec<Todo> {
-    todos
-        .iter()
-        .map(|todo| {
-            if todo.id == id {
-                Todo {
-                    title: title.to_string(),
-                    ..todo.clone()
-                }
-            } else {
-                todo.clone()
-            }
-        })
-        .collect()
-}
+    #[test]
+    fn should_clear_pending_write_after_successful_save() {
+        let mut pending = PendingWrite::default();
+        assert!(pending.attempt("todos", &sample_todos(1), |_, _| Err(())).is_err());
+        assert_eq!(pending.attempt("todos", &sample_todos(2), |_, _| Ok::<(), ()>(())), Ok(()));
+        assert!(!pending.is_pending());
+        assert_eq!(pending.retry(|_, _| Err(())), Ok(()));
+    }
 
-fn clear_edit_state(edit_id_handle: &UseStateHandle<Option<String>>) {
-    edit_id_handle.set(None);
-}
+    #[test]
+    fn should_accept_ctrl_d_on_focused_row() {
+        assert!(is_duplicate_shortcut("d", true, true, false));
+        assert!(is_duplicate_shortcut("D", true, true, false));
+    }
 
-fn set_edit_state(edit_id_handle: &UseStateHandle<Option<String>>, id: &str) {
-    edit_id_handle.set(Some(id.to_string()));
-}
+    #[test]
+    fn should_map_ctrl_z_to_undo_and_ctrl_shift_z_to_redo() {
+        assert_eq!(history_shortcut("z", true, false), Some(HistoryStep::Undo));
+        assert_eq!(history_shortcut("Z", true, true), Some(HistoryStep::Redo));
+        assert_eq!(history_shortcut("z", false, false), None);
+        assert_eq!(history_shortcut("y", true, false), None);
+    }
 
-fn focus_input(input_ref: &NodeRef) {
-    if let Some(input) = input_ref.cast::<HtmlInputElement>() {
-        if input.focus().is_err() {
-            web_sys::console::log_1(&"Failed to focus input".into());
-        }
+    #[test]
+    fn should_reject_duplicate_shortcut_when_ineligible() {
+        assert!(!is_duplicate_shortcut("d", false, true, false));
+        assert!(!is_duplicate_shortcut("d", true, false, false));
+        assert!(!is_duplicate_shortcut("d", true, true, true));
+        assert!(!is_duplicate_shortcut("e", true, true, false));
     }
-}
 
-#[function_component(App)]
-fn app() -> Html {
-    let storage_error = use_state(|| None::<String>);
-    let todos = use_state(|| {
-        match LocalStorage::get(STORAGE_KEY) {
-            Ok(todos) => todos,
-            Err(e) => {
-                storage_error.set(Some(format!("Failed to load todos: {:?}", e)));
-                Vec::<Todo>::new()
-            }
+    fn tagged(id: &str, tags: &[&str], completed: bool) -> Todo {
+        Todo {
+            id: id.to_string(),
+            title: format!("Task {}", id),
+            completed,
+            tags: tags.iter().map(|tag| tag.to_string()).collect(),
+            ..Default::default()
         }
-    });
+    }
 
-    let input_ref = use_node_ref();
-    let edit_id = use_state(|| None::<String>);
-    let edit_input_ref = use_node_ref();
+    #[test]
+    fn should_report_completion_percent_per_tag_sorted_by_name() {
+        let todos = vec![
+            tagged("1", &["work", "urgent"], true),
+            tagged("2", &["work"], false),
+            tagged("3", &["work", "home"], true),
+            tagged("4", &["urgent"], false),
+            tagged("5", &["errands"], false),
+            tagged("6", &[], true),
+        ];
+        let report = tag_report(&todos);
+        assert_eq!(
+            report.iter().map(|stat| stat.tag.as_str()).collect::<Vec<_>>(),
+            vec!["errands", "home", "urgent", "work"]
+        );
+        assert_eq!((report[0].total, report[0].completed), (1, 0));
+        assert_eq!(report[0].percent, 0.0);
+        assert_eq!(report[1].percent, 100.0);
+        assert_eq!(report[2].percent, 50.0);
+        assert_eq!((report[3].total, report[3].completed), (3, 2));
+        assert!((report[3].percent - 66.666).abs() < 0.01);
+    }
 
-    let on_submit = {
-        let todos = todos.clone();
-        let input_ref = input_ref.clone();
-        let storage_error = storage_error.clone();
-        Callback::from(move |e: SubmitEvent| {
-            e.prevent_default();
-            if let Some(input) = input_ref.cast::<HtmlInputElement>() {
-                let title = read_input_title(&input);
-                if is_valid_title(&title) {
-                    let new_todos = create_new_todo(&todos, title);
-                    update_todos(&todos, new_todos, &storage_error);
-                    clear_input(&input);
-                }
-            }
-        })
-    };
+    #[test]
+    fn should_exclude_archived_todos_from_tag_report() {
+        let mut archived = tagged("2", &["work", "old"], true);
+        archived.archived = true;
+        let report = tag_report(&[tagged("1", &["work"], false), archived]);
+        assert_eq!(report.len(), 1);
+        assert_eq!((report[0].total, report[0].completed), (1, 0));
+    }
 
-    let on_delete = {
-        let todos = todos.clone();
-        let storage_error = storage_error.clone();
-        Callback::from(move |id: String| {
-            let new_todos = delete_todo(&todos, &id);
-            update_todos(&todos, new_todos, &storage_error);
-        })
-    };
+    #[test]
+    fn should_export_tag_report_as_csv() {
+        let csv = export_tag_report_csv(&tag_report(&[
+            tagged("1", &["work"], true),
+            tagged("2", &["work"], false),
+            tagged("3", &["work"], false),
+        ]));
+        assert_eq!(csv, "tag,total,completed,percent\r\nwork,3,1,33.3\r\n");
+    }
 
-    let on_toggle = {
-        let todos = todos.clone();
-        let storage_error = storage_error.clone();
-        Callback::from(move |id: String| {
-            let new_todos = toggle_todo(&todos, &id);
-            update_todos(&todos, new_todos, &storage_error);
-        })
-    };
+    #[test]
+    fn should_humanize_sub_minute_distances_as_just_now() {
+        let now = 10.0 * ONE_DAY_MS;
+        assert_eq!(humanize_due(now, now), "just now");
+        assert_eq!(humanize_due(now + 59_000.0, now), "just now");
+        assert_eq!(humanize_due(now - 59_000.0, now), "just now");
+    }
 
-    let on_edit = {
-        let edit_id = edit_id.clone();
-        let edit_input_ref = edit_input_ref.clone();
-        Callback::from(move |id: String| {
-            set_edit_state(&edit_id, &id);
-            focus_input(&edit_input_ref);
-        })
-    };
+    #[test]
+    fn should_humanize_future_due_times() {
+        let now = 10.0 * ONE_DAY_MS;
+        assert_eq!(humanize_due(now + ONE_MINUTE_MS, now), "in 1 minute");
+        assert_eq!(humanize_due(now + 59.0 * ONE_MINUTE_MS, now), "in 59 minutes");
+        assert_eq!(humanize_due(now + ONE_HOUR_MS, now), "in 1 hour");
+        assert_eq!(humanize_due(now + 3.5 * ONE_HOUR_MS, now), "in 3 hours");
+        assert_eq!(humanize_due(now + ONE_DAY_MS - 1.0, now), "in 23 hours");
+        assert_eq!(humanize_due(now + ONE_DAY_MS, now), "in 1 day");
+        assert_eq!(humanize_due(now + 3.0 * ONE_DAY_MS, now), "in 3 days");
+    }
 
-    let on_update = {
-        let todos = todos.clone();
-        let edit_id = edit_id.clone();
-        let edit_input_ref = edit_input_ref.clone();
-        let storage_error = storage_error.clone();
-        Callback::from(move |id: String| {
-            if let Some(input) = edit_input_ref.cast::<HtmlInputElement>() {
-                let title = read_input_title(&input);
-                if is_valid_title(&title) {
-                    let new_todos = update_todo_title(&todos, &id, &title);
-                    update_todos(&todos, new_todos, &storage_error);
-                    clear_edit_state(&edit_id);
-                }
-            }
-        })
-    };
+    #[test]
+    fn should_humanize_past_due_times() {
+        let now = 10.0 * ONE_DAY_MS;
+        assert_eq!(humanize_due(now - ONE_MINUTE_MS, now), "today");
+        assert_eq!(humanize_due(now - ONE_DAY_MS + 1.0, now), "today");
+        assert_eq!(humanize_due(now - ONE_DAY_MS, now), "1 day ago");
+        assert_eq!(humanize_due(now - 2.5 * ONE_DAY_MS, now), "2 days ago");
+    }
 
-    let on_cancel = {
-        let edit_id = edit_id.clone();
-        Callback::from(move |_| clear_edit_state(&edit_id))
-    };
+    #[test]
+    fn should_show_placeholder_for_empty_title() {
+        assert_eq!(display_title(""), ("(no title)".to_string(), true));
+    }
 
-    let render_todo = |id: String, title: String, completed: bool, is_editing: bool| {
-        let id_for_toggle = id.clone();
-        let id_for_edit = id.clone();
-        let id_for_delete = id;
-        html! {
-            <li class="flex items-center p-2 border rounded">
-                if is_editing {
-                    <input
-                        type="text"
-                        ref={edit_input_ref.clone()}
-                        value={title}
-                        class="flex-grow p-1 border rounded focus:outline-none focus:ring-2 focus:ring-blue-500"
-                    />
-                    <button
-                        onclick={on_update.reform(move |_| id_for_edit.clone())}
-                        class={format!("{} {}", BUTTON_CLASS, SAVE_BUTTON)}
-                    >
-                        {"Save"}
-                    </button>
-                    <button
-                        onclick={on_cancel.clone()}
-                        class={format!("{} {}", BUTTON_CLASS, CANCEL_BUTTON)}
-                    >
-                        {"Cancel"}
-                    </button>
-                } else {
-                    <input
-                        type="checkbox"
-                        checked={completed}
-                        onclick={on_toggle.reform(move |_| id_for_toggle.clone())}
-                        class="mr-2"
-                    />
-                    <span class={if completed { "line-through flex-grow" } else { "flex-grow" }}>
-                        { title }
-                    </span>
-                    <button
-                        onclick={on_edit.reform(move |_| id_for_edit.clone())}
-                        class={format!("{} {}", BUTTON_CLASS, EDIT_BUTTON)}
-                    >
-                        {"Edit"}
-                    </button>
-                    <button
-                        onclick={on_delete.reform(move |_| id_for_delete.clone())}
-                        class={format!("{} {}", BUTTON_CLASS, DELETE_BUTTON)}
-                    >
-                        {"Delete"}
-                    </button>
-                }
-            </li>
-        }
-    };
+    #[test]
+    fn should_show_placeholder_for_whitespace_title() {
+        assert_eq!(display_title("  \t "), ("(no title)".to_string(), true));
+    }
 
-    html! {
-        <div class="container mx-auto p-4 max-w-md">
-            <h1 class="text-2xl font-bold mb-4 text-center">{"Todo App"}</h1>
-            <form onsubmit={on_submit} class="mb-4">
-                <div class="flex gap-2">
-                    <input
-                        type="text"
-                        ref={input_ref}
-                        placeholder="Add a new task"
-                        class="flex-grow p-2 border rounded focus:outline-none focus:ring-2 focus:ring-blue-500"
-                    />
-                    <button
-                        type="submit"
-                        class={ADD_BUTTON}
-                    >
-                        {"Add"}
-                    </button>
-                </div>
-            </form>
-            {
-                (*storage_error).as_ref().map_or_else(
-                    || html! {},
-                    |error| html! { <p class="text-red-500">{ error }</p> }
-                )
-            }
-            <ul class="space-y-2">
-                { for (*todos).iter().map(|todo| {
-                    let is_editing = edit_id.as_ref() == Some(&todo.id);
-                    render_todo(todo.id.clone(), todo.title.clone(), todo.completed, is_editing)
-                })}
-            </ul>
-        </div>
+    #[test]
+    fn should_show_normal_title_as_is() {
+        assert_eq!(display_title("Buy milk"), ("Buy milk".to_string(), false));
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+    #[test]
+    fn should_render_todos_as_plain_text_lines() {
+        let mut todos = sample_todos(3);
+        todos[1].completed = true;
+        assert_eq!(todos_to_text(&todos), "[ ] Task 1\n[x] Task 2\n[ ] Task 3");
+    }
 
     #[test]
-    fn should_add_new_todo_to_existing_list() {
-        let todos = vec![Todo {
-            id: "1".to_string(),
-            title: "Create Yew + TW + Rust App".to_string(),
-            completed: false,
-        }];
-        let new_todos = create_new_todo(&todos, "New Task".to_string());
-        assert_eq!(new_todos.len(), 2);
-        assert_eq!(new_todos[1].title, "New Task");
-        assert_eq!(new_todos[1].completed, false);
+    fn should_render_empty_list_as_empty_text() {
+        assert_eq!(todos_to_text(&[]), "");
     }
 
     #[test]
-    fn should_validate_non_empty_title() {
-        assert_eq!(is_valid_title("Welcom Rust"), true);
+    fn should_interpolate_title_into_toggle_label() {
+        assert_eq!(toggle_aria_label("Buy milk", false), "Mark 'Buy milk' complete");
+        assert_eq!(toggle_aria_label("Buy milk", true), "Mark 'Buy milk' incomplete");
     }
 
     #[test]
-    fn should_invalidate_empty_or_whitespace_title() {
-        assert_eq!(is_valid_title(""), false);
-        assert_eq!(is_valid_title("  "), false);
+    fn should_interpolate_title_into_action_labels() {
+        assert_eq!(action_aria_label("Edit", "Walk dog"), "Edit 'Walk dog'");
+        assert_eq!(action_aria_label("Delete", "Walk dog"), "Delete 'Walk dog'");
     }
 
     #[test]
-    fn should_remove_todo_by_id() {
-        let todos = vec![
-            Todo {
-                id: "1".to_string(),
-                title: "Task 1".to_string(),
-                completed: false,
-            },
-            Todo {
-                id: "2".to_string(),
-                title: "Task 2".to_string(),
-                completed: true,
-            },
-        ];
-        let new_todos = delete_todo(&todos, "1");
-        assert_eq!(new_todos.len(), 1);
-        assert_eq!(new_todos[0].id, "2");
-        assert_eq!(new_todos[0].title, "Task 2");
-        assert_eq!(new_todos[0].completed, true);
+    fn should_pluralize_remaining_label() {
+        assert_eq!(remaining_label(0), "0 items left");
+        assert_eq!(remaining_label(1), "1 item left");
+        assert_eq!(remaining_label(4), "4 items left");
     }
 
     #[test]
-    fn should_toggle_todo_completion_status() {
-        let todos = vec![
-            Todo {
-                id: "1".to_string(),
-                title: "Task 1".to_string(),
-                completed: false,
-            },
-            Todo {
-                id: "2".to_string(),
-                title: "Task 2".to_string(),
-                completed: true,
-            },
-        ];
-        let new_todos = toggle_todo(&todos, "1");
-        assert_eq!(new_todos.len(), 2);
-        assert_eq!(new_todos[0].id, "1");
-        assert_eq!(new_todos[0].title, "Task 1");
-        assert_eq!(new_todos[0].completed, true);
-        assert_eq!(new_todos[1].id, "2");
-        assert_eq!(new_todos[1].title, "Task 2");
-        assert_eq!(new_todos[1].completed, true);
+    fn should_detect_markup() {
+        assert!(contains_markup("<script>alert(1)</script>"));
+        assert!(contains_markup("hi </b>"));
+        assert!(contains_markup("<!-- comment -->"));
+        assert!(!contains_markup("1 < 2 and 3 > 2"));
+        assert!(!contains_markup("<3 rust"));
+        assert!(!contains_markup("plain title"));
+    }
+
+    #[test]
+    fn should_reject_import_with_markup() {
+        let mut todos = sample_todos(2);
+        assert!(validate_import(todos.clone()).is_ok());
+        todos[1].tags = vec!["<img src=x>".to_string()];
+        assert!(validate_import(todos.clone()).is_err());
+        todos[1].tags.clear();
+        todos[0].title = "<script>".to_string();
+        assert!(parse_todos_json(&export_todos_json(&todos)).is_err());
     }
 
     #[test]
@@ -370,24 +8093,22 @@ mod tests {
                 id: "1".to_string(),
                 title: "Task 1".to_string(),
                 completed: false,
+                ..Default::default()
             },
             Todo {
                 id: "2".to_string(),
                 title: "Task 2".to_string(),
                 completed: true,
+                ..Default::default()
             },
         ];
-        let new_todos = update_todo_title(&todos, "1", "Updated Task");
+        let new_todos = update_todo_title(&todos, "1", "Updated Task", 0.0);
         assert_eq!(new_todos.len(), 2);
         assert_eq!(new_todos[0].id, "1");
         assert_eq!(new_todos[0].title, "Updated Task");
-        assert_eq!(new_todos[0].completed, false);
+        assert!(!new_todos[0].completed);
         assert_eq!(new_todos[1].id, "2");
         assert_eq!(new_todos[1].title, "Task 2");
-        assert_eq!(new_todos[1].completed, true);
+        assert!(new_todos[1].completed);
     }
 }
-
-fn main() {
-    yew::Renderer::<App>::new().render();
-}
\ No newline at end of file